@@ -1,8 +1,6 @@
-use proc_macro::{Diagnostic, Level};
 use proc_macro2::TokenStream;
-use quote::{format_ident, quote, ToTokens};
-use syn::spanned::Spanned;
-use syn::{GenericParam, ItemTrait};
+use quote::{format_ident, quote};
+use syn::ItemTrait;
 
 pub fn dyn_dyn_base(_args: TokenStream, mut input: ItemTrait) -> TokenStream {
     let vis = input.vis.clone();
@@ -10,24 +8,6 @@ pub fn dyn_dyn_base(_args: TokenStream, mut input: ItemTrait) -> TokenStream {
     let generics = input.generics.clone();
     let (_, type_generics, where_clause) = input.generics.split_for_impl();
 
-    let mut bad_spans = vec![];
-
-    for generic_param in input.generics.params.iter() {
-        if matches!(*generic_param, GenericParam::Lifetime(_)) {
-            bad_spans.push(generic_param.span().unwrap());
-        }
-    }
-
-    if !bad_spans.is_empty() {
-        Diagnostic::spanned(
-            bad_spans,
-            Level::Error,
-            "dyn-dyn base traits cannot have lifetime arguments",
-        )
-        .emit();
-        return input.to_token_stream();
-    }
-
     let base_trait_ident = format_ident!("__dyn_dyn_{}_Base", ident);
     let mut base_trait_impl_generics = generics.clone();
 
@@ -40,6 +20,16 @@ pub fn dyn_dyn_base(_args: TokenStream, mut input: ItemTrait) -> TokenStream {
         .params
         .insert(0, syn::parse2(quote!('__dyn_dyn_lifetime)).unwrap());
     let (impl_generics, _, _) = dyn_dyn_base_generics.split_for_impl();
+    let (static_impl_generics, _, _) = generics.split_for_impl();
+
+    // The target type parameter of the generated methods is normally just called D, but this would conflict with a type parameter of the
+    // base trait having the same name.
+    let tgt = if generics.type_params().any(|p| p.ident == "D") {
+        format_ident!("__dyn_dyn_D")
+    } else {
+        format_ident!("D")
+    };
+    let tgt_bound = quote!(?Sized + ::dyn_dyn::internal::StaticCastTarget);
 
     input
         .supertraits
@@ -67,6 +57,58 @@ pub fn dyn_dyn_base(_args: TokenStream, mut input: ItemTrait) -> TokenStream {
                 <Self as #base_trait_ident #type_generics>::__dyn_dyn_get_table(self)
             }
         }
+
+        // SAFETY: This is the trait object of a trait declared with #[dyn_dyn_base], so its table describes the object that it points to
+        unsafe impl #impl_generics ::dyn_dyn::internal::BaseTraitObject for dyn #ident #type_generics + '__dyn_dyn_lifetime #where_clause {}
+
+        #[allow(dead_code)]
+        impl #static_impl_generics dyn #ident #type_generics + 'static #where_clause {
+            /// Attempts to cast this reference into a reference to the trait object `D`, which must have been exposed by the concrete type
+            /// of this object.
+            #vis fn dyn_dyn_cast_ref<#tgt: #tgt_bound>(&self) -> ::core::option::Option<&#tgt> {
+                // SAFETY: This impl is for the base trait object with a 'static object lifetime bound, as cast_static requires.
+                unsafe { ::dyn_dyn::internal::cast_static::<Self, #tgt, _>(self) }.ok()
+            }
+
+            /// Attempts to cast this mutable reference into a mutable reference to the trait object `D`, which must have been exposed by
+            /// the concrete type of this object.
+            #vis fn dyn_dyn_cast_mut<#tgt: #tgt_bound>(&mut self) -> ::core::option::Option<&mut #tgt> {
+                // SAFETY: Self has a 'static object lifetime bound, as above.
+                unsafe { ::dyn_dyn::internal::cast_static::<Self, #tgt, _>(self) }.ok()
+            }
+
+            /// Returns `true` if this object can be cast to the trait object `D` using [`dyn_dyn_cast_ref`](Self::dyn_dyn_cast_ref).
+            #vis fn dyn_dyn_implements<#tgt: #tgt_bound>(&self) -> bool {
+                // SAFETY: Self has a 'static object lifetime bound, as above.
+                unsafe { ::dyn_dyn::internal::cast_static::<Self, #tgt, _>(self) }.is_ok()
+            }
+
+            ::dyn_dyn::internal::if_alloc! {
+                /// Attempts to cast this `Box` into a `Box` of the trait object `D`, returning the original `Box` on failure.
+                #vis fn dyn_dyn_cast_box<#tgt: #tgt_bound>(
+                    self: ::dyn_dyn::internal::Box<Self>,
+                ) -> ::core::result::Result<::dyn_dyn::internal::Box<#tgt>, ::dyn_dyn::internal::Box<Self>> {
+                    // SAFETY: Self has a 'static object lifetime bound, as above.
+                    unsafe { ::dyn_dyn::internal::cast_static::<Self, #tgt, _>(self) }
+                }
+
+                /// Attempts to cast this `Rc` into an `Rc` of the trait object `D`, returning the original `Rc` on failure.
+                #vis fn dyn_dyn_cast_rc<#tgt: #tgt_bound>(
+                    self: ::dyn_dyn::internal::Rc<Self>,
+                ) -> ::core::result::Result<::dyn_dyn::internal::Rc<#tgt>, ::dyn_dyn::internal::Rc<Self>> {
+                    // SAFETY: Self has a 'static object lifetime bound, as above.
+                    unsafe { ::dyn_dyn::internal::cast_static::<Self, #tgt, _>(self) }
+                }
+
+                /// Attempts to cast this `Arc` into an `Arc` of the trait object `D`, returning the original `Arc` on failure.
+                #vis fn dyn_dyn_cast_arc<#tgt: #tgt_bound>(
+                    self: ::dyn_dyn::internal::Arc<Self>,
+                ) -> ::core::result::Result<::dyn_dyn::internal::Arc<#tgt>, ::dyn_dyn::internal::Arc<Self>> {
+                    // SAFETY: Self has a 'static object lifetime bound, as above.
+                    unsafe { ::dyn_dyn::internal::cast_static::<Self, #tgt, _>(self) }
+                }
+            }
+        }
     };
 
     tokens
@@ -1,11 +1,15 @@
+use crate::lifetimes::{
+    higher_ranked_lifetimes, lifetime_key, map_lifetimes, path_lifetimes, rename_lifetimes,
+};
 use proc_macro::{Diagnostic, Level};
-use proc_macro2::{Delimiter, Group, Span, TokenStream, TokenTree};
-use quote::{quote, TokenStreamExt};
-use syn::parse::{Parse, ParseStream};
+use proc_macro2::{Delimiter, Group, Ident, Span, TokenStream, TokenTree};
+use quote::{format_ident, quote, TokenStreamExt};
+use std::iter;
+use syn::parse::{Parse, ParseStream, Parser};
 use syn::punctuated::Punctuated;
 use syn::spanned::Spanned;
-use syn::token::Bracket;
-use syn::{bracketed, Expr, Token, TraitBound, Type, TypeParamBound};
+use syn::token::{Brace, Bracket, Paren};
+use syn::{braced, bracketed, parenthesized, Expr, Pat, Token, TraitBound, Type, TypeParamBound};
 
 #[derive(Copy, Clone)]
 pub enum DynDynCastType {
@@ -14,31 +18,77 @@ pub enum DynDynCastType {
     Ref,
 }
 
+impl Parse for DynDynCastType {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Ok(if let Some(tok) = input.parse::<Option<Token![mut]>>()? {
+            DynDynCastType::Mut(tok)
+        } else if let Some(tok) = input.parse::<Option<Token![move]>>()? {
+            DynDynCastType::Move(tok)
+        } else {
+            DynDynCastType::Ref
+        })
+    }
+}
+
 pub struct DynDynCastInput {
+    try_: Option<Token![try]>,
     ty: DynDynCastType,
     base_traits: Punctuated<TypeParamBound, Token![+]>,
-    _arrow: Token![=>],
-    target_traits: Punctuated<TypeParamBound, Token![+]>,
+    via_traits: Vec<Punctuated<TypeParamBound, Token![+]>>,
+    target_traits: CastTargets,
     outer_struct: Option<(Bracket, TokenStream)>,
     _comma: Token![,],
     expr: Expr,
 }
 
+enum CastTargets {
+    Single(Punctuated<TypeParamBound, Token![+]>),
+    Multi(
+        Paren,
+        Punctuated<Punctuated<TypeParamBound, Token![+]>, Token![,]>,
+    ),
+}
+
 impl Parse for DynDynCastInput {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let outer_struct;
 
-        Ok(DynDynCastInput {
-            ty: if let Some(tok) = input.parse::<Option<Token![mut]>>()? {
-                DynDynCastType::Mut(tok)
-            } else if let Some(tok) = input.parse::<Option<Token![move]>>()? {
-                DynDynCastType::Move(tok)
+        let try_ = input.parse()?;
+        let ty = input.parse()?;
+        let base_traits = Punctuated::parse_separated_nonempty(input)?;
+        let mut via_traits = vec![];
+
+        let target_traits = loop {
+            input.parse::<Token![=>]>()?;
+
+            if input.peek(Paren) {
+                let targets;
+                let paren = parenthesized!(targets in input);
+
+                break CastTargets::Multi(
+                    paren,
+                    Punctuated::parse_terminated_with(
+                        &targets,
+                        Punctuated::parse_separated_nonempty,
+                    )?,
+                );
+            }
+
+            let target_traits = Punctuated::parse_separated_nonempty(input)?;
+
+            if input.peek(Token![=>]) {
+                via_traits.push(target_traits);
             } else {
-                DynDynCastType::Ref
-            },
-            base_traits: Punctuated::parse_separated_nonempty(input)?,
-            _arrow: input.parse()?,
-            target_traits: Punctuated::parse_separated_nonempty(input)?,
+                break CastTargets::Single(target_traits);
+            }
+        };
+
+        Ok(DynDynCastInput {
+            try_,
+            ty,
+            base_traits,
+            via_traits,
+            target_traits,
             outer_struct: if input.peek(Bracket) {
                 Some((bracketed!(outer_struct in input), outer_struct.parse()?))
             } else {
@@ -52,10 +102,12 @@ impl Parse for DynDynCastInput {
 
 struct DynDynCastProcessedInput {
     val: Expr,
+    try_: bool,
     ty: DynDynCastType,
     base_primary_trait: TraitBound,
-    tgt_primary_trait: TraitBound,
-    tgt_markers: Vec<TypeParamBound>,
+    via_traits: Vec<TraitBound>,
+    tgts: Vec<(TraitBound, Vec<TypeParamBound>)>,
+    multi: bool,
     outer_struct: Option<TokenStream>,
 }
 
@@ -63,6 +115,13 @@ struct DynDynCastProcessedInput {
 enum Error {
     LifetimesNotAllowedInCast,
     BaseMarkerTraitsNotAllowed,
+    OuterStructNotAllowedInChain,
+    OuterStructNotAllowedWithMultipleTargets,
+    MultipleTargetsNotAllowedMut,
+    NoTargets,
+    TargetLifetimeNotInBase,
+    MatchMissingDefault,
+    MatchDefaultNotLast,
 }
 
 fn split_trait_bounds(
@@ -95,14 +154,59 @@ fn process_input(input: &DynDynCastInput) -> Result<DynDynCastProcessedInput, (S
         return Err((base_marker.span(), Error::BaseMarkerTraitsNotAllowed));
     }
 
-    let (tgt_primary_trait, tgt_markers) = split_trait_bounds(&input.target_traits)?;
+    let mut via_traits = vec![];
+
+    for via in input.via_traits.iter() {
+        let (via_primary_trait, via_markers) = split_trait_bounds(via)?;
+        if let Some(via_marker) = via_markers.first() {
+            return Err((via_marker.span(), Error::BaseMarkerTraitsNotAllowed));
+        }
+
+        via_traits.push(via_primary_trait);
+    }
+
+    if let Some((bracket, _)) = input.outer_struct.as_ref() {
+        if !via_traits.is_empty() {
+            return Err((bracket.span, Error::OuterStructNotAllowedInChain));
+        }
+    }
+
+    let (tgts, multi) = match input.target_traits {
+        CastTargets::Single(ref target_traits) => (vec![split_trait_bounds(target_traits)?], false),
+        CastTargets::Multi(paren, ref targets) => {
+            if let Some((bracket, _)) = input.outer_struct.as_ref() {
+                return Err((
+                    bracket.span,
+                    Error::OuterStructNotAllowedWithMultipleTargets,
+                ));
+            }
+
+            if let DynDynCastType::Mut(tok) = input.ty {
+                return Err((tok.span, Error::MultipleTargetsNotAllowedMut));
+            }
+
+            if targets.is_empty() {
+                return Err((paren.span, Error::NoTargets));
+            }
+
+            (
+                targets
+                    .iter()
+                    .map(split_trait_bounds)
+                    .collect::<Result<_, _>>()?,
+                true,
+            )
+        }
+    };
 
     Ok(DynDynCastProcessedInput {
         val: input.expr.clone(),
+        try_: input.try_.is_some(),
         ty: input.ty,
         base_primary_trait,
-        tgt_primary_trait,
-        tgt_markers,
+        via_traits,
+        tgts,
+        multi,
         outer_struct: input
             .outer_struct
             .as_ref()
@@ -161,31 +265,15 @@ pub fn dyn_dyn_cast(input: DynDynCastInput) -> TokenStream {
         Ok(input_parsed) => {
             let DynDynCastProcessedInput {
                 val,
+                try_,
                 ty,
                 base_primary_trait,
-                tgt_primary_trait,
-                tgt_markers,
+                via_traits,
+                tgts,
+                multi,
                 outer_struct,
             } = input_parsed;
 
-            if !tgt_markers.is_empty() {
-                Diagnostic::spanned(
-                    tgt_markers[0].span().unwrap(),
-                    Level::Error,
-                    "casting to traits with extra bounds is not allowed"
-                )
-                .note("this used to be allowed prior to dyn-dyn 0.2, but is liable to cause UB in the future due to https://github.com/rust-lang/rust/issues/127323")
-                .emit();
-
-                return quote!(unreachable!());
-            }
-
-            let helper_new = match ty {
-                DynDynCastType::Mut(_) => quote!(new_mut),
-                DynDynCastType::Move(_) => quote!(new_move),
-                DynDynCastType::Ref => quote!(new_ref),
-            };
-
             if let Some(ref outer_struct) = outer_struct.as_ref() {
                 match replace_placeholder(Some((*outer_struct).clone()), quote!(())) {
                     Ok(outer_struct) => match syn::parse2::<Type>(outer_struct) {
@@ -207,100 +295,641 @@ pub fn dyn_dyn_cast(input: DynDynCastInput) -> TokenStream {
                 }
             }
 
+            let mut via = vec![];
+            let mut prev_trait = &base_primary_trait;
+
+            for via_trait in via_traits.iter() {
+                match lookup_metadata(prev_trait, quote!(dyn #via_trait)) {
+                    Ok((lookup, _)) => via.push((quote!(dyn #via_trait), lookup)),
+                    Err((span, err)) => return emit_error(span, err, "dyn_dyn_cast!"),
+                }
+
+                prev_trait = via_trait;
+            }
+
             let primary_base =
                 replace_placeholder(outer_struct.clone(), quote!((dyn #base_primary_trait)))
                     .unwrap();
-            let tgt_dyn = replace_placeholder(
+            let base_with_lifetime = replace_placeholder(
                 outer_struct.clone(),
-                quote!((dyn #tgt_primary_trait #(+ #tgt_markers)*)),
+                quote!((dyn #base_primary_trait + '__dyn_dyn_life)),
             )
             .unwrap();
 
-            let constrain_lifetime = {
-                let base_with_lifetime = replace_placeholder(
+            let mut targets = vec![];
+
+            for &(ref tgt_primary_trait, ref tgt_markers) in tgts.iter() {
+                let (lookup, key) = match lookup_metadata(
+                    prev_trait,
+                    quote!(dyn #tgt_primary_trait #(+ #tgt_markers)*),
+                ) {
+                    Ok(lookup) => lookup,
+                    Err((span, err)) => return emit_error(span, err, "dyn_dyn_cast!"),
+                };
+
+                let tgt_dyn = replace_placeholder(
                     outer_struct.clone(),
-                    quote!((dyn #base_primary_trait + '__dyn_dyn_life)),
+                    quote!((dyn #tgt_primary_trait #(+ #tgt_markers)*)),
                 )
                 .unwrap();
                 let tgt_with_lifetime = replace_placeholder(
-                    outer_struct,
+                    outer_struct.clone(),
                     quote!((dyn #tgt_primary_trait #(+ #tgt_markers)* + '__dyn_dyn_life)),
                 )
                 .unwrap();
 
-                quote!({
-                    fn __dyn_dyn_constrain_lifetime<
-                        '__dyn_dyn_ref,
-                        '__dyn_dyn_life,
-                        T: ::dyn_dyn::internal::DynDynConstrainLifetime<'__dyn_dyn_ref, #base_with_lifetime>
-                    >(
-                        _: T
-                    ) -> <
-                        T as ::dyn_dyn::internal::DynDynConstrainLifetime<'__dyn_dyn_ref, #base_with_lifetime>
-                    >::Result<#tgt_with_lifetime> {
-                        unreachable!()
-                    }
+                targets.push(CastTarget {
+                    lookup,
+                    key,
+                    error_ty: try_.then(|| erase_lifetimes(tgt_dyn.clone())),
+                    tgt: tgt_dyn,
+                    tgt_with_lifetime,
+                });
+            }
 
-                    ::core::result::Result::Ok(__dyn_dyn_constrain_lifetime(
-                        ::dyn_dyn::internal::DerefHelperEnd::<#primary_base>::unwrap(__dyn_dyn_input)
-                    ))
-                })
-            };
+            expand_cast(CastExpansion {
+                val,
+                ty,
+                primary_base,
+                base_with_lifetime,
+                via,
+                targets,
+                multi,
+            })
+        }
+        Err((span, err)) => emit_error(span, err, "dyn_dyn_cast!"),
+    }
+}
+
+pub struct DynDynDowncastInput {
+    try_: Option<Token![try]>,
+    ty: DynDynCastType,
+    base_traits: Punctuated<TypeParamBound, Token![+]>,
+    _arrow: Token![=>],
+    target: Type,
+    _comma: Token![,],
+    expr: Expr,
+}
+
+impl Parse for DynDynDowncastInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Ok(DynDynDowncastInput {
+            try_: input.parse()?,
+            ty: input.parse()?,
+            base_traits: Punctuated::parse_separated_nonempty(input)?,
+            _arrow: input.parse()?,
+            target: input.parse()?,
+            _comma: input.parse()?,
+            expr: input.parse()?,
+        })
+    }
+}
+
+pub fn dyn_dyn_downcast(input: DynDynDowncastInput) -> TokenStream {
+    let base_primary_trait = match split_trait_bounds(&input.base_traits) {
+        Ok((_, base_markers)) if !base_markers.is_empty() => {
+            return emit_error(
+                base_markers[0].span(),
+                Error::BaseMarkerTraitsNotAllowed,
+                "dyn_dyn_downcast!",
+            );
+        }
+        Ok((base_primary_trait, _)) => base_primary_trait,
+        Err((span, err)) => return emit_error(span, err, "dyn_dyn_downcast!"),
+    };
+    let target = &input.target;
+
+    expand_cast(CastExpansion {
+        val: input.expr.clone(),
+        ty: input.ty,
+        primary_base: quote!((dyn #base_primary_trait)),
+        base_with_lifetime: quote!((dyn #base_primary_trait + '__dyn_dyn_life)),
+        via: vec![],
+        targets: vec![CastTarget {
+            lookup: quote!(__dyn_dyn_table.is::<#target>().then_some(())),
+            key: quote!(#target),
+            tgt: quote!(#target),
+            tgt_with_lifetime: quote!(#target),
+            error_ty: input.try_.map(|_| quote!(#target)),
+        }],
+        multi: false,
+    })
+}
+
+pub struct DynDynMatchInput {
+    ty: DynDynCastType,
+    base_traits: Punctuated<TypeParamBound, Token![+]>,
+    _comma: Token![,],
+    expr: Expr,
+    brace: Brace,
+    arms: Vec<DynDynMatchArm>,
+}
+
+enum DynDynMatchArm {
+    Cast {
+        target_traits: Punctuated<TypeParamBound, Token![+]>,
+        pat: Pat,
+        body: Expr,
+    },
+    Default {
+        pat: Pat,
+        body: Expr,
+    },
+}
+
+impl Parse for DynDynMatchInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let arms_input;
+
+        Ok(DynDynMatchInput {
+            ty: input.parse()?,
+            base_traits: Punctuated::parse_separated_nonempty(input)?,
+            _comma: input.parse()?,
+            expr: Expr::parse_without_eager_brace(input)?,
+            brace: braced!(arms_input in input),
+            arms: {
+                let mut arms = vec![];
+
+                while !arms_input.is_empty() {
+                    arms.push(arms_input.parse()?);
+                }
+
+                arms
+            },
+        })
+    }
+}
+
+impl Parse for DynDynMatchArm {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let fork = input.fork();
+        let is_default = matches!(fork.parse::<Pat>(), Ok(Pat::Wild(_) | Pat::Ident(_)))
+            && fork.peek(Token![=>]);
+
+        let arm = if is_default {
+            let pat = input.parse()?;
+            input.parse::<Token![=>]>()?;
+
+            DynDynMatchArm::Default {
+                pat,
+                body: input.parse()?,
+            }
+        } else {
+            // The target is followed by the pattern to bind the result to in parentheses, e.g. Trait<T>(t). Since this would otherwise
+            // be parsed as a trait bound using parenthesized generic arguments, the tokens making up the target are split off first.
+            let mut target = TokenStream::new();
+
+            while !(input.peek(Paren) && input.peek2(Token![=>])) {
+                if input.is_empty() {
+                    return Err(
+                        input.error("expected a target followed by a pattern in parentheses")
+                    );
+                }
+
+                target.append(input.parse::<TokenTree>()?);
+            }
+
+            let pat_input;
+            parenthesized!(pat_input in input);
+            let pat = pat_input.parse()?;
+            input.parse::<Token![=>]>()?;
+
+            DynDynMatchArm::Cast {
+                target_traits: Punctuated::parse_separated_nonempty.parse2(target)?,
+                pat,
+                body: input.parse()?,
+            }
+        };
+
+        let body = match arm {
+            DynDynMatchArm::Cast { ref body, .. } | DynDynMatchArm::Default { ref body, .. } => {
+                body
+            }
+        };
+
+        if input.peek(Token![,]) {
+            input.parse::<Token![,]>()?;
+        } else if !input.is_empty() && !matches!(*body, Expr::Block(_)) {
+            return Err(input.error("expected `,` following match arm"));
+        }
+
+        Ok(arm)
+    }
+}
+
+pub fn dyn_dyn_match(input: DynDynMatchInput) -> TokenStream {
+    let base_primary_trait = match split_trait_bounds(&input.base_traits) {
+        Ok((_, base_markers)) if !base_markers.is_empty() => {
+            return emit_error(
+                base_markers[0].span(),
+                Error::BaseMarkerTraitsNotAllowed,
+                "dyn_dyn_match!",
+            );
+        }
+        Ok((base_primary_trait, _)) => base_primary_trait,
+        Err((span, err)) => return emit_error(span, err, "dyn_dyn_match!"),
+    };
 
-            quote!((|__dyn_dyn_input| {
-                let __dyn_dyn_table = ::dyn_dyn::internal::DerefHelperEnd::<#primary_base>::get_dyn_dyn_table(&__dyn_dyn_input);
-                if true {
-                    if let ::core::option::Option::Some(__dyn_dyn_metadata) = __dyn_dyn_table.find::<dyn #tgt_primary_trait>() {
-                        // SAFETY:
-                        //
-                        // By the safety invariants of GetDynDynTable<B>, we know that the returned DynDynTable matches the concrete type of
-                        // the pointee, so attaching it to the pointer is valid.
-                        //
-                        // Additionally, the lifetime of the output is constrained by the result of the other side of this if
-                        // statement, where __dyn_dyn_constrain_lifetime is called. By doing this, we ensure that the pointee of the
-                        // output cannot outlive the pointee of the input, so there's no lifetime extension here.
+    let primary_base = quote!((dyn #base_primary_trait));
+    let base_with_lifetime = quote!((dyn #base_primary_trait + '__dyn_dyn_life));
+
+    let (default_pat, default_body) = match input.arms.last() {
+        Some(&DynDynMatchArm::Default { ref pat, ref body }) => (pat, body),
+        _ => {
+            return emit_error(
+                input.brace.span,
+                Error::MatchMissingDefault,
+                "dyn_dyn_match!",
+            )
+        }
+    };
+
+    let mut cast_arms = vec![];
+
+    for arm in input.arms[..input.arms.len() - 1].iter() {
+        let (target_traits, pat, body) = match *arm {
+            DynDynMatchArm::Cast {
+                ref target_traits,
+                ref pat,
+                ref body,
+            } => (target_traits, pat, body),
+            DynDynMatchArm::Default { ref pat, .. } => {
+                return emit_error(pat.span(), Error::MatchDefaultNotLast, "dyn_dyn_match!");
+            }
+        };
+
+        let (tgt_primary_trait, tgt_markers) = match split_trait_bounds(target_traits) {
+            Ok(tgt) => tgt,
+            Err((span, err)) => return emit_error(span, err, "dyn_dyn_match!"),
+        };
+        let tgt = quote!((dyn #tgt_primary_trait #(+ #tgt_markers)*));
+        let tgt_with_lifetime =
+            quote!((dyn #tgt_primary_trait #(+ #tgt_markers)* + '__dyn_dyn_life));
+
+        let (lookup, _) = match lookup_metadata(&base_primary_trait, tgt.clone()) {
+            Ok(lookup) => lookup,
+            Err((span, err)) => return emit_error(span, err, "dyn_dyn_match!"),
+        };
+
+        let constrain_lifetime_fn =
+            constrain_lifetime_fn(base_with_lifetime.clone(), vec![tgt_with_lifetime], false);
+
+        cast_arms.push(quote! {
+            if let ::core::option::Option::Some(__dyn_dyn_metadata) = #lookup {
+                let #pat = {
+                    #constrain_lifetime_fn
+
+                    if true {
+                        // SAFETY: See the SAFETY comment in the expansion of dyn_dyn_cast!, which this mirrors.
                         unsafe {
-                            ::core::result::Result::Ok(::dyn_dyn::internal::DerefHelperEnd::<#primary_base>::downcast_unchecked::<
-                                #tgt_dyn
-                            >(__dyn_dyn_input, __dyn_dyn_metadata))
+                            ::dyn_dyn::internal::DerefHelperEnd::<#primary_base>::downcast_unchecked::<#tgt>(
+                                __dyn_dyn_input,
+                                __dyn_dyn_metadata,
+                            )
                         }
                     } else {
-                        ::core::result::Result::Err(::dyn_dyn::internal::DerefHelperEnd::<#primary_base>::into_err(
-                            __dyn_dyn_input
-                        ))
+                        __dyn_dyn_constrain_lifetime(::dyn_dyn::internal::DerefHelperEnd::<#primary_base>::unwrap(__dyn_dyn_input))
                     }
-                } else {
-                    #constrain_lifetime
+                };
+
+                #body
+            } else
+        });
+    }
+
+    let input_helper = deref_helper(input.ty, &primary_base, &input.expr);
+
+    quote!({
+        let __dyn_dyn_input = #input_helper;
+        #[allow(unused_variables)]
+        let __dyn_dyn_table = ::dyn_dyn::internal::DerefHelperEnd::<#primary_base>::get_dyn_dyn_table(&__dyn_dyn_input);
+
+        #(#cast_arms)* {
+            let #default_pat = ::dyn_dyn::internal::DerefHelperEnd::<#primary_base>::into_err(__dyn_dyn_input);
+
+            #default_body
+        }
+    })
+}
+
+struct CastTarget {
+    /// An expression that looks up the metadata for this target in `__dyn_dyn_table`, returning an `Option`.
+    lookup: TokenStream,
+    /// The type whose `TypeId` this target is stored under in a `DynDynTable`.
+    key: TokenStream,
+    tgt: TokenStream,
+    tgt_with_lifetime: TokenStream,
+    error_ty: Option<TokenStream>,
+}
+
+struct CastExpansion {
+    val: Expr,
+    ty: DynDynCastType,
+    primary_base: TokenStream,
+    base_with_lifetime: TokenStream,
+    via: Vec<(TokenStream, TokenStream)>,
+    targets: Vec<CastTarget>,
+    multi: bool,
+}
+
+fn expand_cast(expansion: CastExpansion) -> TokenStream {
+    let CastExpansion {
+        val,
+        ty,
+        primary_base,
+        base_with_lifetime,
+        via,
+        targets,
+        multi,
+    } = expansion;
+
+    let make_err = |error_ty: Option<&TokenStream>| {
+        let into_err =
+            quote!(::dyn_dyn::internal::DerefHelperEnd::<#primary_base>::into_err(__dyn_dyn_input));
+
+        if let Some(error_ty) = error_ty {
+            quote!(::dyn_dyn::DynDynCastError::new::<#error_ty>(#into_err, __dyn_dyn_table))
+        } else {
+            into_err
+        }
+    };
+
+    // If any of the lookups along a chain fails, the error reports the first target as the one that couldn't be cast to.
+    let chain_err = make_err(targets[0].error_ty.as_ref());
+    let via_lookups = via.iter().map(|&(ref via, ref via_lookup)| {
+        quote! {
+            let __dyn_dyn_table = if let ::core::option::Option::Some(__dyn_dyn_metadata) = #via_lookup {
+                // SAFETY: The metadata was just retrieved from the table of the object that __dyn_dyn_input points to.
+                unsafe {
+                    ::dyn_dyn::internal::DerefHelperChain::<#primary_base>::get_chained_dyn_dyn_table::<#via>(
+                        &__dyn_dyn_input,
+                        __dyn_dyn_metadata
+                    )
                 }
-            })({
-                use ::dyn_dyn::internal::DerefHelperT;
-
-                ::dyn_dyn::internal::DerefHelper::<#primary_base, _>::#helper_new(#val)
-                    .__dyn_dyn_check_dyn_dyn()
-                    .__dyn_dyn_check_ref_mut_dyn_dyn()
-                    .__dyn_dyn_check_ref_dyn_dyn()
-                    .__dyn_dyn_check_deref_mut()
-                    .__dyn_dyn_check_deref()
-            }))
+            } else {
+                return ::core::result::Result::Err(#chain_err);
+            };
         }
-        Err((span, err)) => {
-            let (err, note) = match err {
-                Error::LifetimesNotAllowedInCast => ("Explicit lifetimes are not allowed in dyn_dyn_cast!", None),
-                Error::BaseMarkerTraitsNotAllowed => (
-                    "Marker traits are not allowed on the base trait in a dyn_dyn_cast!",
-                    Some("this used to be allowed prior to dyn-dyn 0.2 to cast to targets with marker traits, but is liable to cause UB in the future due to https://github.com/rust-lang/rust/issues/127323")
-                ),
+    });
+
+    let input = deref_helper(ty, &primary_base, &val);
+    let constrain_lifetime_fn = constrain_lifetime_fn(
+        base_with_lifetime,
+        targets
+            .iter()
+            .map(|target| target.tgt_with_lifetime.clone())
+            .collect(),
+        multi,
+    );
+    let constrain_lifetime = quote!({
+        #constrain_lifetime_fn
+
+        ::core::result::Result::Ok(__dyn_dyn_constrain_lifetime(
+            ::dyn_dyn::internal::DerefHelperEnd::<#primary_base>::unwrap(__dyn_dyn_input)
+        ))
+    });
+
+    let cast = if multi {
+        let keys = targets.iter().map(|target| &target.key);
+        let metadata: Vec<_> = (0..targets.len())
+            .map(|i| format_ident!("__dyn_dyn_metadata_{}", i))
+            .collect();
+        let downcasts = targets.iter().zip(metadata.iter()).enumerate().map(|(i, (target, metadata))| {
+            let tgt = &target.tgt;
+            let inner = if i == targets.len() - 1 {
+                quote!(__dyn_dyn_inner)
+            } else {
+                quote!(::dyn_dyn::internal::clone_stable(&__dyn_dyn_inner))
             };
 
-            let mut d = Diagnostic::spanned(span.unwrap(), Level::Error, err);
-
-            if let Some(note) = note {
-                d = d.note(note);
+            quote!(::dyn_dyn::DowncastUnchecked::downcast_unchecked::<#tgt>(#inner, #metadata.downcast::<#tgt>()))
+        });
+        let (missing, err) = if targets[0].error_ty.is_some() {
+            let errs = targets.iter().enumerate().map(|(i, target)| {
+                let err = make_err(target.error_ty.as_ref());
+                quote!(#i => #err,)
+            });
+
+            (
+                quote!(__dyn_dyn_missing),
+                quote!(match __dyn_dyn_missing {
+                    #(#errs)*
+                    _ => unreachable!(),
+                }),
+            )
+        } else {
+            (quote!(_), make_err(None))
+        };
+
+        quote! {
+            match ::dyn_dyn::internal::find_all(&__dyn_dyn_table, [#(::core::any::TypeId::of::<#keys>()),*]) {
+                ::core::result::Result::Ok([#(#metadata),*]) => {
+                    let __dyn_dyn_inner = ::dyn_dyn::internal::DerefHelperEnd::<#primary_base>::unwrap(__dyn_dyn_input);
+
+                    // SAFETY:
+                    //
+                    // By the safety invariants of GetDynDynTable<B>, we know that the returned DynDynTable matches the concrete type of
+                    // the pointee. Each piece of metadata was stored under the key for its target, so it can be downcast to the target's
+                    // metadata and attached to a clone of the pointer, which points to the same object since the pointer is
+                    // CloneStableDeref.
+                    //
+                    // As with a cast to a single target, the lifetimes of the outputs are constrained by the other side of this if
+                    // statement.
+                    unsafe {
+                        ::core::result::Result::Ok((#(#downcasts,)*))
+                    }
+                }
+                ::core::result::Result::Err(#missing) => ::core::result::Result::Err(#err),
             }
+        }
+    } else {
+        let CastTarget {
+            ref lookup,
+            ref tgt,
+            ref error_ty,
+            ..
+        } = targets[0];
+        let err = make_err(error_ty.as_ref());
+
+        quote! {
+            if let ::core::option::Option::Some(__dyn_dyn_metadata) = #lookup {
+                // SAFETY:
+                //
+                // By the safety invariants of GetDynDynTable<B>, we know that the returned DynDynTable matches the concrete type of
+                // the pointee, so attaching it to the pointer is valid.
+                //
+                // Additionally, the lifetime of the output is constrained by the result of the other side of this if
+                // statement, where __dyn_dyn_constrain_lifetime is called. By doing this, we ensure that the pointee of the
+                // output cannot outlive the pointee of the input, so there's no lifetime extension here.
+                unsafe {
+                    ::core::result::Result::Ok(::dyn_dyn::internal::DerefHelperEnd::<#primary_base>::downcast_unchecked::<
+                        #tgt
+                    >(__dyn_dyn_input, __dyn_dyn_metadata))
+                }
+            } else {
+                ::core::result::Result::Err(#err)
+            }
+        }
+    };
 
-            d.emit();
+    quote!((|__dyn_dyn_input| {
+        let __dyn_dyn_table = ::dyn_dyn::internal::DerefHelperEnd::<#primary_base>::get_dyn_dyn_table(&__dyn_dyn_input);
+        if true {
+            #(#via_lookups)*
 
-            quote!(unreachable!())
+            #cast
+        } else {
+            #constrain_lifetime
         }
+    })(#input))
+}
+
+/// Generates an expression that wraps the value being cast in the appropriate `DerefHelper` for the type of cast being performed.
+fn deref_helper(ty: DynDynCastType, primary_base: &TokenStream, val: &Expr) -> TokenStream {
+    let helper_new = match ty {
+        DynDynCastType::Mut(_) => quote!(new_mut),
+        DynDynCastType::Move(_) => quote!(new_move),
+        DynDynCastType::Ref => quote!(new_ref),
+    };
+
+    quote!({
+        use ::dyn_dyn::internal::DerefHelperT;
+
+        ::dyn_dyn::internal::DerefHelper::<#primary_base, _>::#helper_new(#val)
+            .__dyn_dyn_check_dyn_dyn()
+            .__dyn_dyn_check_ref_mut_pin()
+            .__dyn_dyn_check_ref_mut_dyn_dyn()
+            .__dyn_dyn_check_ref_dyn_dyn()
+            .__dyn_dyn_check_deref_mut()
+            .__dyn_dyn_check_deref()
+    })
+}
+
+/// Generates the `__dyn_dyn_constrain_lifetime` fn, which is never actually called but whose return type is used to constrain the lifetime
+/// of the result of a cast to that of the base trait object being cast. If `multi` is set, this returns a tuple with one element per target.
+fn constrain_lifetime_fn(
+    base_with_lifetime: TokenStream,
+    tgts_with_lifetime: Vec<TokenStream>,
+    multi: bool,
+) -> TokenStream {
+    // Lifetimes named in the traits being cast cannot be referenced from within the nested fn used to constrain the lifetime of the
+    // result, so they need to be replaced with generic lifetime parameters that will be inferred from the input.
+    let (mut renamed, extra_lifetimes) = rename_lifetimes(
+        iter::once(base_with_lifetime)
+            .chain(tgts_with_lifetime)
+            .collect(),
+    );
+    let base_with_lifetime = renamed.remove(0);
+    let results_with_lifetime = renamed.iter().map(|tgt_with_lifetime| {
+        quote!(<T as ::dyn_dyn::internal::DynDynConstrainLifetime<'__dyn_dyn_ref, #base_with_lifetime>>::Result<#tgt_with_lifetime>)
+    });
+    let result_with_lifetime = if multi {
+        quote!((#(#results_with_lifetime,)*))
+    } else {
+        quote!(#(#results_with_lifetime)*)
+    };
+
+    quote! {
+        fn __dyn_dyn_constrain_lifetime<
+            '__dyn_dyn_ref,
+            '__dyn_dyn_life,
+            #(#extra_lifetimes,)*
+            T: ::dyn_dyn::internal::DynDynConstrainLifetime<'__dyn_dyn_ref, #base_with_lifetime>
+        >(
+            _: T
+        ) -> #result_with_lifetime {
+            unreachable!()
+        }
+    }
+}
+
+/// Replaces all lifetimes in the provided type other than those bound by a `for<...>` binder with `'static`, so that its `TypeId` can be
+/// taken.
+fn erase_lifetimes(ty: TokenStream) -> TokenStream {
+    let mut bound_lifetimes = vec![];
+
+    higher_ranked_lifetimes(ty.clone(), &mut bound_lifetimes);
+    map_lifetimes(ty, &mut |ident| {
+        if bound_lifetimes.contains(ident) {
+            None
+        } else {
+            Some(Ident::new("static", ident.span()))
+        }
+    })
+}
+
+/// Generates an expression that looks up the metadata for the target trait object in `__dyn_dyn_table`, along with the type whose
+/// `TypeId` the target is stored under.
+///
+/// Auto traits on the target are part of the key, so `dyn Trait + Send` is only found if it was explicitly exposed. Auto traits on the
+/// pointer being cast (its `GetDynDynTable::DynTarget`) are deliberately not used to fall back to the metadata for `dyn Trait`, since the
+/// metadata for `dyn Trait + Send` isn't guaranteed to match it (rust-lang/rust#127323).
+fn lookup_metadata(
+    base: &TraitBound,
+    tgt: TokenStream,
+) -> Result<(TokenStream, TokenStream), (Span, Error)> {
+    match lifetime_key(tgt.clone(), &path_lifetimes(&base.path)) {
+        Ok(Some((erased, lifetimes))) => {
+            let key = quote!(::dyn_dyn::internal::LifetimeKey<#erased, #lifetimes>);
+
+            Ok((
+                quote!(
+                    // SAFETY: The key was constructed from the target trait in the same way as #[dyn_dyn_impl] constructs it for exposed
+                    //         traits, relative to the lifetime arguments of the base trait that the table was retrieved through.
+                    unsafe { ::dyn_dyn::internal::find_with_lifetimes::<#tgt, #key>(&__dyn_dyn_table) }
+                ),
+                key,
+            ))
+        }
+        Ok(None) => Ok((quote!(__dyn_dyn_table.find::<#tgt>()), tgt)),
+        Err(span) => Err((span, Error::TargetLifetimeNotInBase)),
+    }
+}
+
+fn emit_error(span: Span, err: Error, macro_name: &str) -> TokenStream {
+    let (err, note) = match err {
+        Error::LifetimesNotAllowedInCast => (format!("Explicit lifetimes are not allowed in {}", macro_name), None),
+        Error::BaseMarkerTraitsNotAllowed => (
+            format!("Marker traits are not allowed on the base trait in a {}", macro_name),
+            Some("this used to be allowed prior to dyn-dyn 0.2 to cast to targets with marker traits, but is liable to cause UB in the future due to https://github.com/rust-lang/rust/issues/127323")
+        ),
+        Error::TargetLifetimeNotInBase => (
+            format!("Lifetimes in the target trait of a {} must be 'static or lifetime arguments of the base trait", macro_name),
+            None,
+        ),
+        Error::OuterStructNotAllowedInChain => (
+            format!("An outer struct cannot be used in a chained {}", macro_name),
+            None,
+        ),
+        Error::OuterStructNotAllowedWithMultipleTargets => (
+            format!("An outer struct cannot be used in a {} with multiple targets", macro_name),
+            None,
+        ),
+        Error::MultipleTargetsNotAllowedMut => (
+            format!("Multiple targets cannot be used in a mutable {}", macro_name),
+            Some("each target would produce a mutable reference to the same object"),
+        ),
+        Error::MatchMissingDefault => (
+            format!("The last arm of a {} must be a wildcard or identifier pattern", macro_name),
+            Some("this arm is used when none of the other targets could be cast to"),
+        ),
+        Error::MatchDefaultNotLast => (
+            format!("Only the last arm of a {} can be a wildcard or identifier pattern", macro_name),
+            None,
+        ),
+        Error::NoTargets => (
+            format!("At least one target must be provided in a {} with multiple targets", macro_name),
+            None,
+        ),
+    };
+
+    let mut d = Diagnostic::spanned(span.unwrap(), Level::Error, err);
+
+    if let Some(note) = note {
+        d = d.note(note);
     }
+
+    d.emit();
+
+    quote!(unreachable!())
 }
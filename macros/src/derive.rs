@@ -0,0 +1,108 @@
+use proc_macro::{Diagnostic, Level};
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::{Data, DeriveInput, Fields, GenericParam, Index};
+
+pub fn derive_dyn_dyn_base(input: DeriveInput) -> TokenStream {
+    let fields = match input.data {
+        Data::Struct(ref data) => &data.fields,
+        Data::Enum(ref data) => {
+            Diagnostic::spanned(
+                data.enum_token.span.unwrap(),
+                Level::Error,
+                "Cannot derive DynDynBase for an enum",
+            )
+            .emit();
+            return TokenStream::new();
+        }
+        Data::Union(ref data) => {
+            Diagnostic::spanned(
+                data.union_token.span.unwrap(),
+                Level::Error,
+                "Cannot derive DynDynBase for a union",
+            )
+            .emit();
+            return TokenStream::new();
+        }
+    };
+
+    let (index, last_field) = match *fields {
+        Fields::Named(ref fields) => (fields.named.len(), fields.named.last()),
+        Fields::Unnamed(ref fields) => (fields.unnamed.len(), fields.unnamed.last()),
+        Fields::Unit => (0, None),
+    };
+    let last_field = if let Some(last_field) = last_field {
+        last_field
+    } else {
+        Diagnostic::spanned(
+            input.ident.span().unwrap(),
+            Level::Error,
+            "Cannot derive DynDynBase for a struct without any fields",
+        )
+        .help("DynDynBase can only be derived for structs whose last field is an unsized type implementing DynDynBase")
+        .emit();
+        return TokenStream::new();
+    };
+
+    let field = match last_field.ident {
+        Some(ref ident) => ident.to_token_stream(),
+        None => Index::from(index - 1).to_token_stream(),
+    };
+    let field_ty = &last_field.ty;
+
+    let ident = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    let where_predicates = where_clause.into_iter().flat_map(|w| w.predicates.iter());
+
+    // The bound on DstWrapper checks that the last field is the unsized tail of the struct, so that the metadata of a pointer to the struct
+    // is the same as that of a pointer to the field. This is what allows the struct to be cast by replacing that metadata.
+    quote! {
+        // SAFETY: The table is passed through from the last field of the struct, which has the same lifetime as the struct itself. Its
+        //         concrete type is removed, since it is that of the field rather than the struct, and the struct's own concrete type can't
+        //         be named here.
+        unsafe impl #impl_generics ::dyn_dyn::DynDynBase for #ident #type_generics
+        where
+            #(#where_predicates,)*
+            #field_ty: ::dyn_dyn::DynDynBase,
+            Self: ::dyn_dyn::internal::DstWrapper<#field_ty>,
+        {
+            fn get_dyn_dyn_table(&self) -> ::dyn_dyn::DynDynTable {
+                <#field_ty as ::dyn_dyn::DynDynBase>::get_dyn_dyn_table(&self.#field).without_concrete_type()
+            }
+        }
+    }
+}
+
+pub fn derive_dyn_dyn(input: DeriveInput) -> TokenStream {
+    let ident = &input.ident;
+    let (_, type_generics, where_clause) = input.generics.split_for_impl();
+    let where_predicates = where_clause.into_iter().flat_map(|w| w.predicates.iter());
+
+    // The generic parameters are passed to a macro_rules macro which needs to add its own parameters before them, so they can't be passed
+    // surrounded by angle brackets and defaults need to be removed manually.
+    let params = input.generics.params.iter().map(|param| {
+        let mut param = param.clone();
+
+        match param {
+            GenericParam::Type(ref mut param) => {
+                param.eq_token = None;
+                param.default = None;
+            }
+            GenericParam::Const(ref mut param) => {
+                param.eq_token = None;
+                param.default = None;
+            }
+            GenericParam::Lifetime(_) => {}
+        }
+
+        param
+    });
+
+    quote! {
+        ::dyn_dyn::internal::impl_pointer! {
+            [#(#params,)*]
+            [#ident #type_generics]
+            [#(#where_predicates,)*]
+        }
+    }
+}
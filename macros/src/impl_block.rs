@@ -1,9 +1,10 @@
+use crate::lifetimes::{fill_elided_static_lifetimes, lifetime_key, map_lifetimes, path_lifetimes};
 use proc_macro::{Diagnostic, Level};
-use proc_macro2::TokenStream;
+use proc_macro2::{Ident, TokenStream};
 use quote::{quote, ToTokens};
 use syn::punctuated::Punctuated;
 use syn::spanned::Spanned;
-use syn::{GenericParam, ItemImpl, Token, Type};
+use syn::{GenericParam, Generics, ItemImpl, Token, Type, TypeParamBound, WherePredicate};
 
 pub fn dyn_dyn_impl(args: Punctuated<Type, Token![,]>, input: ItemImpl) -> TokenStream {
     let trait_ = if let Some(ref trait_) = input.trait_ {
@@ -33,23 +34,36 @@ pub fn dyn_dyn_impl(args: Punctuated<Type, Token![,]>, input: ItemImpl) -> Token
     let generics = &input.generics;
     let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
 
-    let mut bad_spans = vec![];
+    let impl_lifetimes: Vec<_> = input
+        .generics
+        .lifetimes()
+        .map(|p| p.lifetime.ident.clone())
+        .collect();
 
-    for generic_param in input.generics.params.iter() {
-        if matches!(*generic_param, GenericParam::Lifetime(_)) {
-            bad_spans.push(generic_param.span().unwrap());
+    // Lifetimes in the self type are erased to 'static when building the table, since TypeId requires a 'static type and the vtables for
+    // the exposed traits do not depend on lifetimes. This includes elided reference lifetimes, e.g. in `impl Base for &str`, which would
+    // otherwise be silently inferred to be 'static. Elided lifetimes within `fn(...)` pointer types and `Fn(...)` sugar are higher-ranked
+    // and must be kept as they are, since e.g. `fn(&u8)` and `fn(&'static u8)` are different types.
+    let mut filled_self_ty = (**self_ty).clone();
+    let mut lifetime_erased = fill_elided_static_lifetimes(&mut filled_self_ty, true);
+    let erased_self_ty = map_lifetimes(filled_self_ty.to_token_stream(), &mut |ident| {
+        if impl_lifetimes.contains(ident) {
+            lifetime_erased = true;
+            Some(Ident::new("static", ident.span()))
+        } else {
+            None
         }
-    }
+    });
 
-    if !bad_spans.is_empty() {
-        Diagnostic::spanned(
-            bad_spans,
-            Level::Error,
-            "dyn-dyn implementors cannot have lifetime arguments",
-        )
-        .emit();
-        return input.to_token_stream();
-    }
+    // Elided reference lifetimes in the exposed traits would be inferred to be 'static when building the table, but could be inferred to
+    // be any lifetime in the checks below. Making them explicit ensures that the checks verify the same traits that the table records.
+    let args: Vec<_> = args
+        .into_iter()
+        .map(|mut ty| {
+            fill_elided_static_lifetimes(&mut ty, false);
+            ty
+        })
+        .collect();
 
     let num_table_entries = args.len();
 
@@ -59,10 +73,72 @@ pub fn dyn_dyn_impl(args: Punctuated<Type, Token![,]>, input: ItemImpl) -> Token
         Some(quote!(::))
     };
 
-    let convert_tys = args.iter();
+    let base_lifetimes = path_lifetimes(&trait_);
+    let mut table_entries = vec![];
+    let mut exposes_lifetimes = false;
+
+    for ty in args.iter() {
+        // The name is taken from the source text where possible so that it matches how the trait was written, since stringify! loses the
+        // spacing of tokens passed through a procedural macro.
+        let name = match ty.span().source_text() {
+            Some(text) => format!("dyn {}", text).into_token_stream(),
+            None => quote!(::core::stringify!(dyn #ty)),
+        };
+
+        match lifetime_key(quote!(dyn #ty), &base_lifetimes) {
+            Ok(Some((erased_ty, lifetimes))) => {
+                exposes_lifetimes = true;
+                table_entries.push(quote!(
+                    ::dyn_dyn::DynDynTableEntry::new_with_lifetimes::<#erased_self_ty, #erased_ty, #lifetimes, _>(#name)
+                ));
+            }
+            Ok(None) => {
+                table_entries.push(quote!(
+                    ::dyn_dyn::DynDynTableEntry::new::<#erased_self_ty, dyn #ty, _>(#name)
+                ));
+            }
+            Err(span) => {
+                Diagnostic::spanned(
+                    span.unwrap(),
+                    Level::Error,
+                    "lifetimes in exposed traits must be 'static or lifetime arguments of the base trait",
+                )
+                .emit();
+                return input.to_token_stream();
+            }
+        }
+    }
+
+    // A table that isn't lifetime-erased claims that Self is exactly the concrete type recorded in it, which allows downcasting into it.
+    // Naming Self here rather than the self type as written makes the compiler check that Self really is 'static, so that any lifetimes
+    // the checks above missed can't be silently inferred to be 'static.
+    //
+    // Self can only be named there if it's known to be 'static, which requires every type parameter of the impl to be bound by 'static.
+    // Otherwise the table records no concrete type at all, so it can still be cast from but not downcast into.
+    let table_new = if !type_params_static(&input.generics) {
+        quote!(new_unknown)
+    } else if lifetime_erased {
+        quote!(new_lifetime_erased::<#erased_self_ty>)
+    } else {
+        quote!(new::<Self>)
+    };
+
+    // If the self type or any of the exposed traits have lifetimes, then we also need to make sure that the exposed traits are implemented
+    // for every choice of those lifetimes and not just for 'static. Coercing self here ensures this, since this function is generic over
+    // them.
+    let lifetime_checks = if lifetime_erased || exposes_lifetimes {
+        let check_tys = args.iter();
+        quote!(#(let _: &(dyn #check_tys) = self;)*)
+    } else {
+        quote!()
+    };
 
     let marker_contents = input.generics.params.iter().filter_map(|p| match *p {
-        GenericParam::Type(ref p) => Some(p.ident.clone()),
+        GenericParam::Type(ref p) => Some(p.ident.to_token_stream()),
+        GenericParam::Lifetime(ref p) => {
+            let lifetime = &p.lifetime;
+            Some(quote!(::core::marker::PhantomData<&#lifetime ()>))
+        }
         _ => None,
     });
     let marker_contents = quote!(#(#marker_contents),*);
@@ -72,24 +148,45 @@ pub fn dyn_dyn_impl(args: Punctuated<Type, Token![,]>, input: ItemImpl) -> Token
 
         // SAFETY: The returned DynDynTable does not depend on data in self at all, so get_dyn_dyn_table will always return the same table
         //         as long as the metadata pointer is not changed in an unsafe way. All entries in the table have valid metadata for this
-        //         type since they were retrieved by performing a trivial unsized coercion on a *const Self.
+        //         type since they were retrieved by performing a trivial unsized coercion on a *const Self. If Self has lifetimes, this
+        //         coercion is performed on Self with its lifetimes erased, and likewise for exposed traits with lifetimes. This produces
+        //         identical metadata since the coercions of self below ensure that the exposed traits are implemented regardless of what
+        //         those lifetimes are.
         unsafe impl #impl_generics ::dyn_dyn::internal::DynDynImpl<dyn #trait_> for #self_ty #where_clause {
             fn get_dyn_dyn_table(&self) -> ::dyn_dyn::DynDynTable {
+                #lifetime_checks
+
                 #[allow(non_camel_case_types)]
                 struct __dyn_dyn_DynTable #generics(#marker_contents) #where_clause;
 
                 impl #impl_generics __dyn_dyn_DynTable #type_generics #where_clause {
                     pub const __TABLE: [::dyn_dyn::DynDynTableEntry; #num_table_entries] = [
-                        #(
-                            ::dyn_dyn::DynDynTableEntry::new::<#self_ty, dyn #convert_tys, _>()
-                        ),*
+                        #(#table_entries),*
                     ];
                 }
 
-                ::dyn_dyn::DynDynTable::new(&__dyn_dyn_DynTable #turbo_tok #type_generics::__TABLE[..])
+                ::dyn_dyn::DynDynTable::#table_new(&__dyn_dyn_DynTable #turbo_tok #type_generics::__TABLE[..])
             }
         }
     };
 
     tokens
 }
+
+/// Returns `true` if every type parameter in the provided generics is bound by `'static`, either in its own bounds or in the where clause.
+fn type_params_static(generics: &Generics) -> bool {
+    let is_static = |bound: &TypeParamBound| matches!(*bound, TypeParamBound::Lifetime(ref lifetime) if lifetime.ident == "static");
+
+    generics.type_params().all(|param| {
+        param.bounds.iter().any(is_static)
+            || generics.where_clause.iter().any(|where_clause| {
+                where_clause.predicates.iter().any(|predicate| match *predicate {
+                    WherePredicate::Type(ref predicate) => {
+                        matches!(predicate.bounded_ty, Type::Path(ref ty) if ty.qself.is_none() && ty.path.is_ident(&param.ident))
+                            && predicate.bounds.iter().any(is_static)
+                    }
+                    _ => false,
+                })
+            })
+    })
+}
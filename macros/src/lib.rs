@@ -7,20 +7,32 @@
 
 extern crate proc_macro;
 
-use crate::cast::DynDynCastInput;
+use crate::cast::{DynDynCastInput, DynDynDowncastInput, DynDynMatchInput};
 use syn::parse::{Parse, ParseStream};
 use syn::punctuated::Punctuated;
-use syn::{parse_macro_input, ItemImpl, ItemTrait, Token, Type};
+use syn::{parse_macro_input, DeriveInput, ItemImpl, ItemTrait, Token, Type};
 
 mod base;
 mod cast;
+mod derive;
 mod impl_block;
+mod lifetimes;
 
 #[proc_macro]
 pub fn dyn_dyn_cast(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     cast::dyn_dyn_cast(parse_macro_input!(input as DynDynCastInput)).into()
 }
 
+#[proc_macro]
+pub fn dyn_dyn_downcast(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    cast::dyn_dyn_downcast(parse_macro_input!(input as DynDynDowncastInput)).into()
+}
+
+#[proc_macro]
+pub fn dyn_dyn_match(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    cast::dyn_dyn_match(parse_macro_input!(input as DynDynMatchInput)).into()
+}
+
 #[proc_macro_attribute]
 pub fn dyn_dyn_base(
     args: proc_macro::TokenStream,
@@ -48,3 +60,13 @@ pub fn dyn_dyn_impl(
     )
     .into()
 }
+
+#[proc_macro_derive(DynDynBase)]
+pub fn derive_dyn_dyn_base(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    derive::derive_dyn_dyn_base(parse_macro_input!(input as DeriveInput)).into()
+}
+
+#[proc_macro_derive(DynDyn)]
+pub fn derive_dyn_dyn(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    derive::derive_dyn_dyn(parse_macro_input!(input as DeriveInput)).into()
+}
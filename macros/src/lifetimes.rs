@@ -0,0 +1,232 @@
+use proc_macro2::{Group, Ident, Punct, Spacing, Span, TokenStream, TokenTree};
+use quote::{quote, TokenStreamExt};
+use syn::visit_mut::{self, VisitMut};
+use syn::{
+    GenericArgument, Lifetime, ParenthesizedGenericArguments, Path, PathArguments, Type,
+    TypeBareFn, TypeReference,
+};
+
+/// Rewrites every lifetime appearing in the provided tokens by passing its name (without the leading `'`) to `f`. Returning `None` from
+/// `f` leaves the lifetime unchanged.
+pub fn map_lifetimes(
+    input: TokenStream,
+    f: &mut impl FnMut(&Ident) -> Option<Ident>,
+) -> TokenStream {
+    let mut out = TokenStream::new();
+    let mut iter = input.into_iter().peekable();
+
+    while let Some(tt) = iter.next() {
+        match tt {
+            TokenTree::Group(group) => {
+                let mut new_group = Group::new(group.delimiter(), map_lifetimes(group.stream(), f));
+                new_group.set_span(group.span());
+                out.append(new_group);
+            }
+            TokenTree::Punct(ref p) if p.as_char() == '\'' && p.spacing() == Spacing::Joint => {
+                if let Some(TokenTree::Ident(ident)) = iter.peek().cloned() {
+                    iter.next();
+                    out.append(tt);
+                    out.append(f(&ident).unwrap_or(ident));
+                } else {
+                    out.append(tt);
+                }
+            }
+            tt => out.append(tt),
+        }
+    }
+
+    out
+}
+
+/// Inserts an explicit `'static` lifetime into every reference in the provided type whose lifetime was elided, e.g. turning
+/// `Iterator<Item = &str>` into `Iterator<Item = &'static str>`. If `placeholders` is set, `'_` is replaced with `'static` as well. References
+/// in the arguments of `Fn(...)` sugar and `fn(...)` pointer types are left alone, since their elided lifetimes are higher-ranked rather than
+/// `'static`. Returns `true` if any lifetime was filled in.
+pub fn fill_elided_static_lifetimes(ty: &mut Type, placeholders: bool) -> bool {
+    struct FillElided {
+        placeholders: bool,
+        filled: bool,
+    }
+
+    impl VisitMut for FillElided {
+        fn visit_type_reference_mut(&mut self, ty: &mut TypeReference) {
+            if ty.lifetime.is_none() {
+                ty.lifetime = Some(Lifetime::new("'static", ty.and_token.span));
+                self.filled = true;
+            }
+
+            visit_mut::visit_type_reference_mut(self, ty);
+        }
+
+        fn visit_lifetime_mut(&mut self, lifetime: &mut Lifetime) {
+            if self.placeholders && lifetime.ident == "_" {
+                *lifetime = Lifetime::new("'static", lifetime.span());
+                self.filled = true;
+            }
+        }
+
+        fn visit_parenthesized_generic_arguments_mut(
+            &mut self,
+            _: &mut ParenthesizedGenericArguments,
+        ) {
+        }
+
+        fn visit_type_bare_fn_mut(&mut self, _: &mut TypeBareFn) {}
+    }
+
+    let mut visitor = FillElided {
+        placeholders,
+        filled: false,
+    };
+
+    visitor.visit_type_mut(ty);
+    visitor.filled
+}
+
+/// Renames all lifetimes in the provided token streams, other than `'static`, those starting with `__dyn_dyn` and those bound by a `for<...>`
+/// binder, to fresh lifetimes. Lifetimes with the same name across all streams will be renamed to the same fresh lifetime, while each `'_`
+/// will be renamed to a distinct lifetime. Returns the renamed streams along with the list of fresh lifetimes that were introduced.
+pub fn rename_lifetimes(inputs: Vec<TokenStream>) -> (Vec<TokenStream>, Vec<TokenStream>) {
+    let mut renamed: Vec<(Ident, Ident)> = vec![];
+    let mut fresh = vec![];
+    let mut bound_lifetimes = vec![];
+
+    for input in inputs.iter() {
+        higher_ranked_lifetimes(input.clone(), &mut bound_lifetimes);
+    }
+
+    let outputs = inputs
+        .into_iter()
+        .map(|input| {
+            map_lifetimes(input, &mut |ident| {
+                let name = ident.to_string();
+
+                if name == "static"
+                    || name.starts_with("__dyn_dyn")
+                    || bound_lifetimes.contains(ident)
+                {
+                    return None;
+                }
+
+                if name != "_" {
+                    if let Some((_, new_ident)) = renamed.iter().find(|(old, _)| *old == *ident) {
+                        return Some(new_ident.clone());
+                    }
+                }
+
+                let new_ident =
+                    Ident::new(&format!("__dyn_dyn_lt{}", fresh.len()), Span::call_site());
+                let mut lifetime = TokenStream::new();
+
+                lifetime.append(Punct::new('\'', Spacing::Joint));
+                lifetime.append(new_ident.clone());
+                fresh.push(lifetime);
+
+                if name != "_" {
+                    renamed.push((ident.clone(), new_ident.clone()));
+                }
+
+                Some(new_ident)
+            })
+        })
+        .collect();
+
+    (outputs, fresh)
+}
+
+/// Collects the names of all lifetimes that are bound by a `for<...>` binder anywhere in the provided tokens, e.g. `'x` in
+/// `for<'x> Fn(&'x T)`.
+pub fn higher_ranked_lifetimes(input: TokenStream, out: &mut Vec<Ident>) {
+    let mut iter = input.into_iter().peekable();
+
+    while let Some(tt) = iter.next() {
+        match tt {
+            TokenTree::Group(group) => higher_ranked_lifetimes(group.stream(), out),
+            TokenTree::Ident(ref ident) if *ident == "for" => {
+                if !matches!(iter.peek(), Some(TokenTree::Punct(ref p)) if p.as_char() == '<') {
+                    continue;
+                }
+
+                iter.next();
+
+                for tt in iter.by_ref() {
+                    match tt {
+                        TokenTree::Punct(ref p) if p.as_char() == '>' => break,
+                        TokenTree::Ident(ident) => out.push(ident),
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Gets the lifetime arguments of the last segment of the provided path, e.g. `['a, 'b]` for `Trait<'a, 'b, T>`.
+pub fn path_lifetimes(path: &Path) -> Vec<Ident> {
+    match path.segments.last().map(|segment| &segment.arguments) {
+        Some(PathArguments::AngleBracketed(ref args)) => args
+            .args
+            .iter()
+            .filter_map(|arg| match *arg {
+                GenericArgument::Lifetime(ref lifetime) => Some(lifetime.ident.clone()),
+                _ => None,
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Computes the key that a trait object type with lifetime arguments is stored under in a `DynDynTable`, returning the trait object type
+/// with all lifetimes replaced with `'static` along with a tuple type recording where each lifetime came from. These are used as the
+/// parameters of `LifetimeKey`. Returns `Ok(None)` if the trait object type has no lifetimes
+/// other than `'static`, in which case the trait object type itself is used as the key.
+///
+/// Every lifetime in the trait object type other than `'static` must be one of the lifetime arguments of the base trait, which are passed
+/// in as `base_lifetimes`, or be bound by a `for<...>` binder within the type, e.g. `for<'x> Fn(&'x T)`. Since higher-ranked lifetimes
+/// don't depend on any outside lifetimes, they are left unchanged and don't contribute to the key. If this is not the case, returns the
+/// span of the first offending lifetime.
+pub fn lifetime_key(
+    ty: TokenStream,
+    base_lifetimes: &[Ident],
+) -> Result<Option<(TokenStream, TokenStream)>, Span> {
+    let mut markers = vec![];
+    let mut has_non_static = false;
+    let mut bad_span = None;
+    let mut bound_lifetimes = vec![];
+
+    higher_ranked_lifetimes(ty.clone(), &mut bound_lifetimes);
+
+    let erased = map_lifetimes(ty, &mut |ident| {
+        if bound_lifetimes.contains(ident) {
+            return None;
+        }
+
+        if *ident == "static" {
+            markers.push(quote!(::dyn_dyn::internal::StaticLifetime));
+            return None;
+        }
+
+        has_non_static = true;
+
+        match base_lifetimes
+            .iter()
+            .position(|base_ident| *ident != "_" && *base_ident == *ident)
+        {
+            Some(idx) => markers.push(quote!(::dyn_dyn::internal::LifetimeParam<#idx>)),
+            None => {
+                bad_span.get_or_insert(ident.span());
+            }
+        }
+
+        Some(Ident::new("static", ident.span()))
+    });
+
+    if let Some(bad_span) = bad_span {
+        Err(bad_span)
+    } else if has_non_static {
+        Ok(Some((erased, quote!((#(#markers,)*)))))
+    } else {
+        Ok(None)
+    }
+}
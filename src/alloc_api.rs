@@ -0,0 +1,198 @@
+// Implementations of GetDynDynTable and DowncastUnchecked for Box, Rc, Arc and their weak pointers with custom allocators. When the
+// allocator-api feature is enabled, these replace the implementations in lib.rs that only support the global allocator. Casting such a
+// pointer with move keeps its allocator, e.g. Box<dyn Base, A> is cast into Box<dyn Trait, A>.
+
+use crate::{DowncastUnchecked, DynDynBase, DynDynTable, GetDynDynTable};
+use alloc::alloc::Allocator;
+use alloc::boxed::Box;
+use alloc::rc::{self, Rc};
+use alloc::sync::{self, Arc};
+use core::marker::Unsize;
+use core::pin::Pin;
+use core::ptr::{self, Pointee};
+
+// SAFETY: Box<T, A> meets all Deref/DerefMut stability requirements and the table is retrieved by dereferencing it, which is correct by
+//         definition. The DowncastUnchecked implementation is also a simple cast via converting to/from a pointer and so should be correct.
+unsafe impl<B: ?Sized + DynDynBase, T: ?Sized + Unsize<B>, A: Allocator> GetDynDynTable<B>
+    for Box<T, A>
+{
+    type DynTarget = T;
+
+    fn get_dyn_dyn_table(&self) -> DynDynTable {
+        B::get_dyn_dyn_table(&**self)
+    }
+}
+
+impl<'a, T: ?Sized + 'a, A: Allocator + 'a> DowncastUnchecked<'a> for Box<T, A> {
+    type DowncastResult<D: ?Sized + 'a> = Box<D, A>;
+
+    unsafe fn downcast_unchecked<D: ?Sized + Pointee>(
+        self,
+        metadata: <D as Pointee>::Metadata,
+    ) -> Box<D, A> {
+        let (ptr, alloc) = Box::into_raw_with_allocator(self);
+
+        // SAFETY: Box::from_raw_in is fine since the fat pointer passed in has the same data pointer as what we got from
+        //         Box::into_raw_with_allocator along with the same allocator, and the metadata pointer is guaranteed to be valid by this fn's
+        //         safety invariants.
+        unsafe { Box::from_raw_in(ptr::from_raw_parts_mut(ptr as *mut (), metadata), alloc) }
+    }
+}
+
+impl<'a, T: ?Sized + 'a, A: Allocator + 'a> DowncastUnchecked<'a> for Pin<Box<T, A>> {
+    type DowncastResult<D: ?Sized + 'a> = Pin<Box<D, A>>;
+
+    unsafe fn downcast_unchecked<D: ?Sized + Pointee>(
+        self,
+        metadata: <D as Pointee>::Metadata,
+    ) -> Pin<Box<D, A>> {
+        // SAFETY: The object is never moved out of the Box, and the downcast Box owns the same allocation as the original pinned Box, so it
+        //         remains pinned. The validity of the metadata is guaranteed by this fn's safety invariants.
+        unsafe { Pin::new_unchecked(Pin::into_inner_unchecked(self).downcast_unchecked(metadata)) }
+    }
+}
+
+// SAFETY: Rc<T, A> meets all Deref/DerefMut stability requirements and the table is retrieved by dereferencing it, which is correct by
+//         definition. The DowncastUnchecked implementation is also a simple cast via converting to/from a pointer and so should be correct.
+unsafe impl<B: ?Sized + DynDynBase, T: ?Sized + Unsize<B>, A: Allocator> GetDynDynTable<B>
+    for Rc<T, A>
+{
+    type DynTarget = T;
+
+    fn get_dyn_dyn_table(&self) -> DynDynTable {
+        B::get_dyn_dyn_table(&**self)
+    }
+}
+
+impl<'a, T: ?Sized + 'a, A: Allocator + 'a> DowncastUnchecked<'a> for Rc<T, A> {
+    type DowncastResult<D: ?Sized + 'a> = Rc<D, A>;
+
+    unsafe fn downcast_unchecked<D: ?Sized + Pointee>(
+        self,
+        metadata: <D as Pointee>::Metadata,
+    ) -> Rc<D, A> {
+        let (ptr, alloc) = Rc::into_raw_with_allocator(self);
+
+        // SAFETY: Rc::from_raw_in is fine since the fat pointer passed in has the same data pointer as what we got from
+        //         Rc::into_raw_with_allocator along with the same allocator, and the metadata pointer is guaranteed to be valid by this fn's
+        //         safety invariants.
+        unsafe { Rc::from_raw_in(ptr::from_raw_parts(ptr as *const (), metadata), alloc) }
+    }
+}
+
+impl<'a, T: ?Sized + 'a, A: Allocator + 'a> DowncastUnchecked<'a> for Pin<Rc<T, A>> {
+    type DowncastResult<D: ?Sized + 'a> = Pin<Rc<D, A>>;
+
+    unsafe fn downcast_unchecked<D: ?Sized + Pointee>(
+        self,
+        metadata: <D as Pointee>::Metadata,
+    ) -> Pin<Rc<D, A>> {
+        // SAFETY: The downcast Rc shares the same allocation as the original pinned Rc, so the object remains pinned. The validity of the
+        //         metadata is guaranteed by this fn's safety invariants.
+        unsafe { Pin::new_unchecked(Pin::into_inner_unchecked(self).downcast_unchecked(metadata)) }
+    }
+}
+
+// SAFETY: rc::Weak<T, A> does not implement Deref, so the only requirement is that metadata retrieved from the returned table is valid for
+//         its pointee. The table is either retrieved from the live pointee through an upgraded pointer, which is correct by definition, or
+//         is empty if the pointee has already been dropped. The DowncastUnchecked implementation is also a simple cast via converting
+//         to/from a pointer and so should be correct.
+unsafe impl<B: ?Sized + DynDynBase, T: ?Sized + Unsize<B>, A: Allocator + Clone> GetDynDynTable<B>
+    for rc::Weak<T, A>
+{
+    type DynTarget = T;
+
+    fn get_dyn_dyn_table(&self) -> DynDynTable {
+        match self.upgrade() {
+            Some(rc) => B::get_dyn_dyn_table(&*rc),
+            None => DynDynTable::DROPPED,
+        }
+    }
+}
+
+impl<'a, T: ?Sized + 'a, A: Allocator + 'a> DowncastUnchecked<'a> for rc::Weak<T, A> {
+    type DowncastResult<D: ?Sized + 'a> = rc::Weak<D, A>;
+
+    unsafe fn downcast_unchecked<D: ?Sized + Pointee>(
+        self,
+        metadata: <D as Pointee>::Metadata,
+    ) -> rc::Weak<D, A> {
+        let (ptr, alloc) = rc::Weak::into_raw_with_allocator(self);
+
+        // SAFETY: rc::Weak::from_raw_in is fine since the fat pointer passed in has the same data pointer as what we got from
+        //         rc::Weak::into_raw_with_allocator along with the same allocator, and the metadata pointer is guaranteed to be valid by
+        //         this fn's safety invariants.
+        unsafe { rc::Weak::from_raw_in(ptr::from_raw_parts(ptr as *const (), metadata), alloc) }
+    }
+}
+
+// SAFETY: Arc<T, A> meets all Deref/DerefMut stability requirements and the table is retrieved by dereferencing it, which is correct by
+//         definition. The DowncastUnchecked implementation is also a simple cast via converting to/from a pointer and so should be correct.
+unsafe impl<B: ?Sized + DynDynBase, T: ?Sized + Unsize<B>, A: Allocator> GetDynDynTable<B>
+    for Arc<T, A>
+{
+    type DynTarget = T;
+
+    fn get_dyn_dyn_table(&self) -> DynDynTable {
+        B::get_dyn_dyn_table(&**self)
+    }
+}
+
+impl<'a, T: ?Sized + 'a, A: Allocator + 'a> DowncastUnchecked<'a> for Arc<T, A> {
+    type DowncastResult<D: ?Sized + 'a> = Arc<D, A>;
+
+    unsafe fn downcast_unchecked<D: ?Sized + Pointee>(
+        self,
+        metadata: <D as Pointee>::Metadata,
+    ) -> Arc<D, A> {
+        let (ptr, alloc) = Arc::into_raw_with_allocator(self);
+
+        // SAFETY: Arc::from_raw_in is fine since the fat pointer passed in has the same data pointer as what we got from
+        //         Arc::into_raw_with_allocator along with the same allocator, and the metadata pointer is guaranteed to be valid by this
+        //         fn's safety invariants.
+        unsafe { Arc::from_raw_in(ptr::from_raw_parts(ptr as *const (), metadata), alloc) }
+    }
+}
+
+impl<'a, T: ?Sized + 'a, A: Allocator + 'a> DowncastUnchecked<'a> for Pin<Arc<T, A>> {
+    type DowncastResult<D: ?Sized + 'a> = Pin<Arc<D, A>>;
+
+    unsafe fn downcast_unchecked<D: ?Sized + Pointee>(
+        self,
+        metadata: <D as Pointee>::Metadata,
+    ) -> Pin<Arc<D, A>> {
+        // SAFETY: The downcast Arc shares the same allocation as the original pinned Arc, so the object remains pinned. The validity of the
+        //         metadata is guaranteed by this fn's safety invariants.
+        unsafe { Pin::new_unchecked(Pin::into_inner_unchecked(self).downcast_unchecked(metadata)) }
+    }
+}
+
+// SAFETY: See the SAFETY comment on the implementation for rc::Weak<T, A>, which also applies to sync::Weak<T, A>.
+unsafe impl<B: ?Sized + DynDynBase, T: ?Sized + Unsize<B>, A: Allocator + Clone> GetDynDynTable<B>
+    for sync::Weak<T, A>
+{
+    type DynTarget = T;
+
+    fn get_dyn_dyn_table(&self) -> DynDynTable {
+        match self.upgrade() {
+            Some(arc) => B::get_dyn_dyn_table(&*arc),
+            None => DynDynTable::DROPPED,
+        }
+    }
+}
+
+impl<'a, T: ?Sized + 'a, A: Allocator + 'a> DowncastUnchecked<'a> for sync::Weak<T, A> {
+    type DowncastResult<D: ?Sized + 'a> = sync::Weak<D, A>;
+
+    unsafe fn downcast_unchecked<D: ?Sized + Pointee>(
+        self,
+        metadata: <D as Pointee>::Metadata,
+    ) -> sync::Weak<D, A> {
+        let (ptr, alloc) = sync::Weak::into_raw_with_allocator(self);
+
+        // SAFETY: sync::Weak::from_raw_in is fine since the fat pointer passed in has the same data pointer as what we got from
+        //         sync::Weak::into_raw_with_allocator along with the same allocator, and the metadata pointer is guaranteed to be valid by
+        //         this fn's safety invariants.
+        unsafe { sync::Weak::from_raw_in(ptr::from_raw_parts(ptr as *const (), metadata), alloc) }
+    }
+}
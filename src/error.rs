@@ -0,0 +1,112 @@
+use crate::DynDynTable;
+use core::any::{self, TypeId};
+use core::fmt::{self, Debug, Display};
+
+#[cfg(doc)]
+use crate::dyn_dyn_cast;
+
+/// An error returned by a failed cast that describes why the cast failed.
+///
+/// Errors of this type are returned by [`dyn_dyn_cast!`] and [`dyn_dyn_downcast!`](crate::dyn_dyn_downcast) when the `try` keyword is
+/// used, e.g. `dyn_dyn_cast!(try Base => Trait, r)`. In addition to the pointer that was originally passed in, they record the type that
+/// was requested and the [`DynDynTable`] of the object that couldn't be cast to it.
+pub struct DynDynCastError<P> {
+    ptr: P,
+    target: TypeId,
+    target_name: &'static str,
+    table: DynDynTable,
+}
+
+impl<P> DynDynCastError<P> {
+    /// Creates a new error for a failed cast of `ptr`, which points to an object with the provided table, to a pointer to `D`.
+    ///
+    /// If `D` has lifetime parameters, then it should be replaced with the same type with all of its lifetime parameters replaced by
+    /// `'static`.
+    pub fn new<D: ?Sized + 'static>(ptr: P, table: DynDynTable) -> Self {
+        DynDynCastError {
+            ptr,
+            target: TypeId::of::<D>(),
+            target_name: any::type_name::<D>(),
+            table,
+        }
+    }
+
+    /// Gets a reference to the pointer that failed to be cast.
+    pub fn get_ref(&self) -> &P {
+        &self.ptr
+    }
+
+    /// Unwraps this error, returning the pointer that failed to be cast.
+    pub fn into_inner(self) -> P {
+        self.ptr
+    }
+
+    /// Gets the [`TypeId`] of the type that the pointer was being cast to.
+    pub fn target_type_id(&self) -> TypeId {
+        self.target
+    }
+
+    /// Gets a human-readable name representing the type that the pointer was being cast to.
+    pub fn target_type_name(&self) -> &'static str {
+        self.target_name
+    }
+
+    /// Gets the [`DynDynTable`] of the object that the pointer points to.
+    pub fn table(&self) -> DynDynTable {
+        self.table
+    }
+
+    /// Gets the [`TypeId`] of the concrete type of the object that the pointer points to.
+    pub fn concrete_type_id(&self) -> TypeId {
+        self.table.type_id()
+    }
+
+    /// Gets a human-readable name representing the concrete type of the object that the pointer points to.
+    #[cfg(feature = "dynamic-names")]
+    pub fn concrete_type_name(&self) -> &'static str {
+        self.table.type_name()
+    }
+}
+
+impl<P> Debug for DynDynCastError<P> {
+    #[cfg(feature = "dynamic-names")]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DynDynCastError")
+            .field("target", &self.target_name)
+            .field("concrete", &self.table.type_name())
+            .finish_non_exhaustive()
+    }
+
+    #[cfg(not(feature = "dynamic-names"))]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DynDynCastError")
+            .field("target", &self.target_name)
+            .field("concrete", &self.table.type_id())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<P> Display for DynDynCastError<P> {
+    #[cfg(feature = "dynamic-names")]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "cannot cast object of type `{}` to `{}`",
+            self.table.type_name(),
+            self.target_name
+        )
+    }
+
+    #[cfg(not(feature = "dynamic-names"))]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "cannot cast object of type {:?} to `{}`",
+            self.table.type_id(),
+            self.target_name
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl<P> std::error::Error for DynDynCastError<P> {}
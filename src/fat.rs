@@ -1,11 +1,12 @@
-use crate::{DowncastUnchecked, DynDynBase, DynDynTable, GetDynDynTable};
+use crate::{DowncastUnchecked, DynDynBase, DynDynBaseObject, DynDynTable, GetDynDynTable};
+use cfg_if::cfg_if;
 use core::cmp::Ordering;
 use core::fmt::{self, Display, Pointer};
 use core::hash::{Hash, Hasher};
 use core::marker::{PhantomData, Unsize};
 use core::ops::CoerceUnsized;
 use core::ops::{Deref, DerefMut};
-use core::ptr::{self, Pointee};
+use core::ptr::{self, DynMetadata, Pointee};
 use stable_deref_trait::{CloneStableDeref, StableDeref};
 
 /// A fat pointer to an object that can be downcast via the base trait object `B`.
@@ -83,6 +84,110 @@ where
     }
 }
 
+impl<B: ?Sized + DynDynBaseObject + 'static, P: GetDynDynTable<B>> DynDynFat<B, P> {
+    /// Attempts to cast a fat pointer to a pointer to the trait object `D` using its cached [`DynDynTable`]. On success, the result of
+    /// downcasting the wrapped pointer is returned. Otherwise, the original fat pointer is returned unchanged.
+    ///
+    /// As with [`cast`](crate::cast), both `B` and `D` must be `'static` and `B` must be a base trait object rather than a wrapper around
+    /// one.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use core::fmt::Debug;
+    /// # use dyn_dyn::{dyn_dyn_base, dyn_dyn_impl, DynDynFat};
+    /// #[dyn_dyn_base]
+    /// trait Base {}
+    ///
+    /// #[derive(Debug)]
+    /// struct Struct;
+    ///
+    /// #[dyn_dyn_impl(Debug)]
+    /// impl Base for Struct {}
+    ///
+    /// let fat: DynDynFat<dyn Base, &dyn Base> = DynDynFat::new(&Struct);
+    /// let debug: &dyn Debug = DynDynFat::cast(fat).ok().unwrap();
+    ///
+    /// assert_eq!("Struct", format!("{:?}", debug));
+    /// ```
+    pub fn cast<'a, D>(ptr: Self) -> Result<<P as DowncastUnchecked<'a>>::DowncastResult<D>, Self>
+    where
+        P: DowncastUnchecked<'a>,
+        D: ?Sized + Pointee<Metadata = DynMetadata<D>> + 'static,
+    {
+        if let Some(metadata) = ptr.table.find::<D>() {
+            // SAFETY: The cached table was retrieved from the pointer, and since B is a base trait object rather than a wrapper around one,
+            //         it describes the object that the pointer points to, so attaching metadata retrieved from it is valid. Since B is
+            //         'static, the pointee must also be 'static and so casting it to a 'static trait object does not extend any lifetimes.
+            Ok(unsafe { ptr.ptr.downcast_unchecked::<D>(metadata) })
+        } else {
+            Err(ptr)
+        }
+    }
+
+    /// Attempts to cast a fat pointer to a pointer to another base trait object `B2`, producing a fat pointer for that base. The
+    /// [`DynDynTable`] for `B2` is retrieved once during the cast, so the resulting fat pointer can be cast further without any additional
+    /// calls to [`DynDynBase::get_dyn_dyn_table`].
+    ///
+    /// As with [`DynDynFat::cast`], both `B` and `B2` must be `'static` base trait objects.
+    pub fn cast_fat<'a, B2>(
+        ptr: Self,
+    ) -> Result<DynDynFat<B2, <P as DowncastUnchecked<'a>>::DowncastResult<B2>>, Self>
+    where
+        P: DowncastUnchecked<'a>,
+        B2: ?Sized + DynDynBaseObject + 'static,
+        <P as DowncastUnchecked<'a>>::DowncastResult<B2>: GetDynDynTable<B2>,
+    {
+        DynDynFat::cast::<B2>(ptr).map(DynDynFat::new)
+    }
+}
+
+impl<B: ?Sized + DynDynBaseObject + 'static, P: GetDynDynTable<B> + Deref> DynDynFat<B, P>
+where
+    P::Target: Unsize<B>,
+{
+    /// Attempts to cast the object referenced by a fat pointer to a reference to the trait object `D` using its cached [`DynDynTable`].
+    pub fn cast_ref<D>(ptr: &Self) -> Result<&D, &Self>
+    where
+        D: ?Sized + Pointee<Metadata = DynMetadata<D>> + 'static,
+    {
+        if let Some(metadata) = ptr.table.find::<D>() {
+            // SAFETY: Since B is a base trait object rather than a wrapper around one, the cached table matches the object that the pointer
+            //         dereferences to, so attaching metadata retrieved from it is valid. See DynDynFat::cast for why the 'static bound on D is
+            //         fine.
+            Ok(unsafe {
+                <&P::Target as DowncastUnchecked>::downcast_unchecked::<D>(&*ptr.ptr, metadata)
+            })
+        } else {
+            Err(ptr)
+        }
+    }
+}
+
+impl<B: ?Sized + DynDynBaseObject + 'static, P: GetDynDynTable<B> + DerefMut> DynDynFat<B, P>
+where
+    P::Target: Unsize<B>,
+{
+    /// Attempts to cast the object referenced by a fat pointer to a mutable reference to the trait object `D` using its cached
+    /// [`DynDynTable`].
+    pub fn cast_mut<D>(ptr: &mut Self) -> Result<&mut D, &mut Self>
+    where
+        D: ?Sized + Pointee<Metadata = DynMetadata<D>> + 'static,
+    {
+        if let Some(metadata) = ptr.table.find::<D>() {
+            // SAFETY: See DynDynFat::cast_ref.
+            Ok(unsafe {
+                <&mut P::Target as DowncastUnchecked>::downcast_unchecked::<D>(
+                    &mut *ptr.ptr,
+                    metadata,
+                )
+            })
+        } else {
+            Err(ptr)
+        }
+    }
+}
+
 impl<B: ?Sized + DynDynBase, P: Clone> DynDynFat<B, P> {
     /// Clones a fat pointer without verifying that the [`DynDynTable`] held by the new fat pointer is applicable to the cloned pointer.
     ///
@@ -99,6 +204,64 @@ impl<B: ?Sized + DynDynBase, P: Clone> DynDynFat<B, P> {
     }
 }
 
+cfg_if! {
+    if #[cfg(feature = "alloc")] {
+        use alloc::rc::{self, Rc};
+        use alloc::sync::{self, Arc};
+
+        impl<B: ?Sized + DynDynBase, T: ?Sized> DynDynFat<B, Rc<T>> {
+            /// Creates a new [`rc::Weak`] fat pointer to the object referenced by a fat pointer, keeping its cached [`DynDynTable`]. The
+            /// resulting fat pointer can still be cast after the object has been dropped. Since the cast uses the cached table, it succeeds
+            /// whenever it would have succeeded while the object was live, but the resulting [`rc::Weak`] can no longer be upgraded.
+            pub fn downgrade_rc(ptr: &Self) -> DynDynFat<B, rc::Weak<T>> {
+                DynDynFat {
+                    ptr: Rc::downgrade(&ptr.ptr),
+                    table: ptr.table,
+                    _base: PhantomData,
+                }
+            }
+        }
+
+        impl<B: ?Sized + DynDynBase, T: ?Sized> DynDynFat<B, rc::Weak<T>> {
+            /// Attempts to upgrade a weak fat pointer to an [`Rc`] fat pointer, keeping its cached [`DynDynTable`]. Returns `None` if the
+            /// object has already been dropped.
+            pub fn upgrade_rc(ptr: &Self) -> Option<DynDynFat<B, Rc<T>>> {
+                Some(DynDynFat {
+                    ptr: ptr.ptr.upgrade()?,
+                    table: ptr.table,
+                    _base: PhantomData,
+                })
+            }
+        }
+
+        impl<B: ?Sized + DynDynBase, T: ?Sized> DynDynFat<B, Arc<T>> {
+            /// Creates a new [`sync::Weak`] fat pointer to the object referenced by a fat pointer, keeping its cached [`DynDynTable`].
+            /// The resulting fat pointer can still be cast after the object has been dropped. Since the cast uses the cached table, it
+            /// succeeds whenever it would have succeeded while the object was live, but the resulting [`sync::Weak`] can no longer be
+            /// upgraded.
+            pub fn downgrade_arc(ptr: &Self) -> DynDynFat<B, sync::Weak<T>> {
+                DynDynFat {
+                    ptr: Arc::downgrade(&ptr.ptr),
+                    table: ptr.table,
+                    _base: PhantomData,
+                }
+            }
+        }
+
+        impl<B: ?Sized + DynDynBase, T: ?Sized> DynDynFat<B, sync::Weak<T>> {
+            /// Attempts to upgrade a weak fat pointer to an [`Arc`] fat pointer, keeping its cached [`DynDynTable`]. Returns `None` if the
+            /// object has already been dropped.
+            pub fn upgrade_arc(ptr: &Self) -> Option<DynDynFat<B, Arc<T>>> {
+                Some(DynDynFat {
+                    ptr: ptr.ptr.upgrade()?,
+                    table: ptr.table,
+                    _base: PhantomData,
+                })
+            }
+        }
+    }
+}
+
 // SAFETY: The table returned by this implementation was retrieved from the pointer at the time the DynDynFat was created and DynDynFat does
 //         not expose any way to mutate the pointer itself. Additionally, DynTarget is simply passed through from the pointer, so it must be
 //         valid for that pointer.
@@ -0,0 +1,57 @@
+use crate::{DowncastUnchecked, DynDynBase, DynDynTable, GetDynDynTable};
+use cfg_if::cfg_if;
+use core::cell::{Ref, RefMut};
+use core::marker::Unsize;
+use core::ptr::Pointee;
+
+// Guards dereference to the object that they guard, which never changes for the lifetime of the guard, so they meet the Deref stability
+// requirements and the table is retrieved by dereferencing them, which is correct by definition. The DowncastUnchecked implementations
+// use the guard's map function to replace the guarded reference with one produced by the implementation for references, so they should be
+// correct as well.
+macro_rules! impl_guard {
+    ($guard:ident, $result:ident, $map:path, $ref_ty:ty) => {
+        // SAFETY: See the comment above this macro.
+        unsafe impl<'a, B: ?Sized + DynDynBase, T: ?Sized + Unsize<B>> GetDynDynTable<B>
+            for $guard<'a, T>
+        {
+            type DynTarget = T;
+
+            fn get_dyn_dyn_table(&self) -> DynDynTable {
+                B::get_dyn_dyn_table(&**self)
+            }
+        }
+
+        impl<'a, T: ?Sized + 'a> DowncastUnchecked<'a> for $guard<'a, T> {
+            type DowncastResult<D: ?Sized + 'a> = $result<'a, D>;
+
+            unsafe fn downcast_unchecked<D: ?Sized + Pointee>(
+                self,
+                metadata: <D as Pointee>::Metadata,
+            ) -> $result<'a, D> {
+                $map(self, |r| {
+                    // SAFETY: The reference being mapped refers to the same object as the guard, so the validity of the metadata is
+                    //         guaranteed by this fn's safety invariants.
+                    unsafe { <$ref_ty as DowncastUnchecked>::downcast_unchecked(r, metadata) }
+                })
+            }
+        }
+    };
+}
+
+impl_guard!(Ref, Ref, Ref::map, &T);
+impl_guard!(RefMut, RefMut, RefMut::map, &mut T);
+
+cfg_if! {
+    if #[cfg(feature = "std")] {
+        use std::sync::{
+            MappedMutexGuard, MappedRwLockReadGuard, MappedRwLockWriteGuard, MutexGuard, RwLockReadGuard, RwLockWriteGuard,
+        };
+
+        impl_guard!(MutexGuard, MappedMutexGuard, MutexGuard::map, &mut T);
+        impl_guard!(MappedMutexGuard, MappedMutexGuard, MappedMutexGuard::map, &mut T);
+        impl_guard!(RwLockReadGuard, MappedRwLockReadGuard, RwLockReadGuard::map, &T);
+        impl_guard!(MappedRwLockReadGuard, MappedRwLockReadGuard, MappedRwLockReadGuard::map, &T);
+        impl_guard!(RwLockWriteGuard, MappedRwLockWriteGuard, RwLockWriteGuard::map, &mut T);
+        impl_guard!(MappedRwLockWriteGuard, MappedRwLockWriteGuard, MappedRwLockWriteGuard::map, &mut T);
+    }
+}
@@ -0,0 +1,170 @@
+use crate::{DynDynBase, DynDynTable, GetDynDynTable};
+use core::cell::Cell;
+use core::fmt::{self, Debug, Display};
+use core::marker::{PhantomData, Unsize};
+use core::mem::{self, MaybeUninit};
+use core::ops::{Deref, DerefMut};
+use core::ptr::{self, DynMetadata, Pointee};
+
+#[repr(C, align(16))]
+struct InlineStorage<const N: usize>(MaybeUninit<[u8; N]>);
+
+/// An owning container that stores an object that can be downcast via the base trait object `B` inline, without any heap allocation.
+///
+/// The object is stored in a buffer of `N` bytes aligned to 16 bytes, alongside its trait object metadata and its [`DynDynTable`]. The table
+/// is retrieved the first time it is needed and cached from then on, so further casts of a reference to the container using
+/// [`dyn_dyn_cast!`](crate::dyn_dyn_cast) do not call [`DynDynBase::get_dyn_dyn_table`]. Since the object is moved along with the
+/// container, only casting by reference and by mutable reference are supported.
+///
+/// Since retrieving the table is deferred, [`DynDynInline::new`] is a `const fn`, so containers can be created in constants and in the
+/// initializers of thread-local statics. The container is `Send` if `B` is, i.e. if the base trait has `Send` as a supertrait, but it is
+/// never `Sync`, since the cached table is stored in a [`Cell`] that is filled in through a shared reference. Storing one in a `static`
+/// therefore requires a wrapper that synchronizes access to it.
+///
+/// Attempting to store an object that is larger than `N` bytes or has an alignment greater than 16 bytes fails to compile.
+///
+/// # Examples
+///
+/// ```rust
+/// # use dyn_dyn::{dyn_dyn_base, dyn_dyn_cast, dyn_dyn_impl, DynDynInline};
+/// #[dyn_dyn_base]
+/// trait Driver {}
+///
+/// trait Uart {
+///     fn write(&mut self, byte: u8);
+/// }
+///
+/// struct Pl011(u8);
+///
+/// #[dyn_dyn_impl(Uart)]
+/// impl Driver for Pl011 {}
+///
+/// impl Uart for Pl011 {
+///     fn write(&mut self, byte: u8) {
+///         self.0 = byte;
+///     }
+/// }
+///
+/// struct Timer(u64);
+///
+/// #[dyn_dyn_impl]
+/// impl Driver for Timer {}
+///
+/// let mut drivers: [DynDynInline<dyn Driver, 16>; 2] = [DynDynInline::new(Pl011(0)), DynDynInline::new(Timer(0))];
+///
+/// for driver in &mut drivers {
+///     if let Ok(uart) = dyn_dyn_cast!(mut Driver => Uart, driver) {
+///         uart.write(b'!');
+///     }
+/// }
+/// ```
+pub struct DynDynInline<B: ?Sized + DynDynBase + Pointee<Metadata = DynMetadata<B>>, const N: usize>
+{
+    metadata: DynMetadata<B>,
+    table: Cell<Option<DynDynTable>>,
+    storage: InlineStorage<N>,
+    _value: PhantomData<B>,
+}
+
+impl<B: ?Sized + DynDynBase + Pointee<Metadata = DynMetadata<B>>, const N: usize>
+    DynDynInline<B, N>
+{
+    /// Moves the provided value into a new inline container. Its [`DynDynTable`] is retrieved and cached the first time it is needed.
+    pub const fn new<T: Unsize<B>>(value: T) -> Self {
+        const {
+            assert!(
+                mem::size_of::<T>() <= N,
+                "object is too large to be stored in this DynDynInline"
+            );
+            assert!(
+                mem::align_of::<T>() <= mem::align_of::<InlineStorage<N>>(),
+                "object is too strictly aligned to be stored in a DynDynInline"
+            );
+        }
+
+        let metadata = ptr::metadata(&value as &B as *const B);
+        let mut storage = InlineStorage(MaybeUninit::uninit());
+
+        // SAFETY: The assertions above guarantee that the storage is large enough and sufficiently aligned to hold a T.
+        unsafe { storage.0.as_mut_ptr().cast::<T>().write(value) };
+
+        DynDynInline {
+            metadata,
+            table: Cell::new(None),
+            storage,
+            _value: PhantomData,
+        }
+    }
+
+    /// Gets the [`DynDynTable`] of the object stored in an inline container. This only dereferences the object if its table hasn't been
+    /// retrieved yet.
+    pub fn get_dyn_dyn_table(ptr: &Self) -> DynDynTable {
+        match ptr.table.get() {
+            Some(table) => table,
+            None => {
+                let table = B::get_dyn_dyn_table(&**ptr);
+                ptr.table.set(Some(table));
+                table
+            }
+        }
+    }
+}
+
+impl<B: ?Sized + DynDynBase + Pointee<Metadata = DynMetadata<B>>, const N: usize> Deref
+    for DynDynInline<B, N>
+{
+    type Target = B;
+
+    fn deref(&self) -> &B {
+        // SAFETY: The storage always holds a live object whose concrete type matches the metadata, which was written when the container was
+        //         created.
+        unsafe { &*ptr::from_raw_parts(self.storage.0.as_ptr(), self.metadata) }
+    }
+}
+
+impl<B: ?Sized + DynDynBase + Pointee<Metadata = DynMetadata<B>>, const N: usize> DerefMut
+    for DynDynInline<B, N>
+{
+    fn deref_mut(&mut self) -> &mut B {
+        // SAFETY: See above. The container owns the object, so borrowing it mutably through &mut self is fine.
+        unsafe { &mut *ptr::from_raw_parts_mut(self.storage.0.as_mut_ptr(), self.metadata) }
+    }
+}
+
+impl<B: ?Sized + DynDynBase + Pointee<Metadata = DynMetadata<B>>, const N: usize> Drop
+    for DynDynInline<B, N>
+{
+    fn drop(&mut self) {
+        // SAFETY: The container owns the object and is being dropped, so nothing else can observe it afterwards.
+        unsafe { ptr::drop_in_place(&mut **self as *mut B) }
+    }
+}
+
+// SAFETY: The table is either retrieved from the object or was cached after doing so, and the table of an object can never change through its
+//         lifetime. Deref only returns a different address after the container has been moved, which GetDynDynTable<B> allows, and the
+//         object moves along with it, so it still refers to the same object with the same metadata.
+unsafe impl<B: ?Sized + DynDynBase + Pointee<Metadata = DynMetadata<B>> + Unsize<B>, const N: usize>
+    GetDynDynTable<B> for DynDynInline<B, N>
+{
+    type DynTarget = B;
+
+    fn get_dyn_dyn_table(&self) -> DynDynTable {
+        DynDynInline::get_dyn_dyn_table(self)
+    }
+}
+
+impl<B: ?Sized + DynDynBase + Pointee<Metadata = DynMetadata<B>> + Debug, const N: usize> Debug
+    for DynDynInline<B, N>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl<B: ?Sized + DynDynBase + Pointee<Metadata = DynMetadata<B>> + Display, const N: usize> Display
+    for DynDynInline<B, N>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&**self, f)
+    }
+}
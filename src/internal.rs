@@ -8,11 +8,16 @@
 // parameters due to how these structs need to work.
 #![allow(clippy::type_complexity)]
 
-use crate::{DowncastUnchecked, DynDyn, DynDynBase, DynDynRef, DynDynRefMut, DynDynTable};
+use crate::{
+    AnyDynMetadata, DowncastUnchecked, DynDyn, DynDynBase, DynDynCastTarget, DynDynPointer,
+    DynDynRef, DynDynRefMut, DynDynTable, GetDynDynTable,
+};
+use core::any::TypeId;
 use core::marker::{PhantomData, Unsize};
 use core::ops::{Deref, DerefMut};
-use core::ptr::Pointee;
-use stable_deref_trait::StableDeref;
+use core::pin::Pin;
+use core::ptr::{self, DynMetadata, Pointee};
+use stable_deref_trait::CloneStableDeref;
 
 // This special proxy trait is needed for the __dyn_dyn_constrain_lifetime method which is
 // generated internally by the dyn_dyn_cast! macro to constrain the lifetime of its result. While
@@ -31,6 +36,209 @@ impl<'a, B: ?Sized + DynDynBase, T: DynDyn<'a, B>> DynDynConstrainLifetime<'a, B
     type Result<D: ?Sized + 'a> = <T as DowncastUnchecked<'a>>::DowncastResult<D>;
 }
 
+// Trait objects whose traits have non-'static lifetime arguments (e.g. dyn Visitor<'de>) are keyed in a DynDynTable by the TypeId of
+// LifetimeKey<D, L>, where D is the trait object with all of its lifetimes replaced by 'static and L is a tuple with one entry per lifetime
+// in the trait object recording which lifetime argument of the base trait it refers to. Since a cast knows the lifetime arguments of the
+// base trait, this allows those lifetimes to be reattached without the possibility of lifetime extension.
+pub struct LifetimeKey<D: ?Sized, L>(PhantomData<L>, PhantomData<D>);
+
+pub struct LifetimeParam<const N: usize>;
+
+pub struct StaticLifetime;
+
+/// # Safety
+///
+/// `K` must be a `LifetimeKey<E, L>`, where `E` is `D` with its lifetimes replaced by `'static` and `L` records which lifetime arguments of
+/// the base trait that the pointer being cast was retrieved from correspond to the lifetimes of `D`.
+pub unsafe fn find_with_lifetimes<D: ?Sized + DynDynCastTarget, K: 'static>(
+    table: &DynDynTable,
+) -> Option<DynMetadata<D>> {
+    table.find_untyped(TypeId::of::<K>()).map(|meta| {
+        // SAFETY: The metadata was stored for E, which differs from D only in its lifetimes and so has identical metadata. The lifetimes
+        //         of D match those used to expose E by the safety invariants of this fn.
+        unsafe { meta.downcast() }
+    })
+}
+
+// Used by dyn_dyn_cast! with multiple targets to look up the metadata for all of the targets in a single pass over the table. Returns the
+// index of the first target that couldn't be found if any are missing.
+pub fn find_all<const N: usize>(
+    table: &DynDynTable,
+    type_ids: [TypeId; N],
+) -> Result<[AnyDynMetadata; N], usize> {
+    let mut found = [None; N];
+
+    for entry in *table {
+        for (found, &type_id) in found.iter_mut().zip(type_ids.iter()) {
+            if found.is_none() && entry.type_id() == type_id {
+                *found = Some(entry.metadata());
+            }
+        }
+    }
+
+    match found.iter().position(Option::is_none) {
+        Some(missing) => Err(missing),
+        None => Ok(found.map(Option::unwrap)),
+    }
+}
+
+// Used by dyn_dyn_cast! with multiple targets, which attaches metadata retrieved from the table of the pointer being cast to clones of that
+// pointer. This is only valid if each clone points to the same object, which CloneStableDeref guarantees but Clone alone does not.
+pub fn clone_stable<P: CloneStableDeref>(ptr: &P) -> P {
+    ptr.clone()
+}
+
+// Used by the methods that #[dyn_dyn_base] generates on the base trait object. Since those methods are generic over the target trait, they
+// have no way to tie the lifetime of the resulting trait object to that of the base trait object. Instead, they are only generated for the
+// base trait object with a 'static object lifetime bound, whose concrete type must therefore be 'static, in which case the result can
+// safely be 'static as well. The base trait itself may still have non-'static lifetime arguments, so this can't be expressed as B: 'static.
+//
+// # Safety
+//
+// The object lifetime bound of B must be 'static, e.g. B = dyn Base<'a> + 'static.
+pub unsafe fn cast_static<'a, B, D, P>(
+    ptr: P,
+) -> Result<<P as DowncastUnchecked<'a>>::DowncastResult<D>, P>
+where
+    B: ?Sized + DynDynBase,
+    D: ?Sized + StaticCastTarget,
+    P: DynDyn<'a, B>,
+{
+    if let Some(metadata) = ptr.get_dyn_dyn_table().find::<D>() {
+        // SAFETY: By the safety invariants of GetDynDynTable<B>, the table matches the concrete type of the pointee, so attaching metadata
+        //         retrieved from it to the pointer is valid. Since the object lifetime bound of B is 'static, the concrete type that was
+        //         unsized into it must be 'static as well, so casting it to a 'static trait object does not extend any lifetimes.
+        Ok(unsafe { ptr.downcast_unchecked::<D>(metadata) })
+    } else {
+        Err(ptr)
+    }
+}
+
+// Bounds for the target of the methods generated by #[dyn_dyn_base]. This is a separate trait so that the generated code doesn't need to
+// name Pointee, which would require the ptr_metadata feature to be enabled in the crate using the macro.
+pub trait StaticCastTarget: Pointee<Metadata = DynMetadata<Self>> + 'static {}
+
+impl<D: ?Sized + Pointee<Metadata = DynMetadata<D>> + 'static> StaticCastTarget for D {}
+
+// Implemented by #[dyn_dyn_base] for the trait objects of the base traits that it declares, and for nothing else. The functions that attach
+// metadata found in a table directly to the data pointer of the pointer being cast, e.g. dyn_dyn::cast, require this of their base. For a
+// wrapper implementing DynDynBase such as Envelope<dyn Base>, the table describes the wrapped field rather than the object at that data
+// pointer, so such a cast would produce a trait object over the wrong memory. dyn_dyn_cast! doesn't need this, since it wraps the target in
+// the same outer struct as the base.
+//
+// # Safety
+//
+// Self must be the trait object of a trait declared with #[dyn_dyn_base], so that the table retrieved through it describes the object that
+// a pointer to Self points to.
+#[allow(clippy::missing_safety_doc)] // This module is marked doc(hidden)
+pub unsafe trait BaseTraitObject:
+    DynDynBase + Pointee<Metadata = DynMetadata<Self>>
+{
+}
+
+// Used by #[derive(DynDynBase)] to check that the field that the table is retrieved from is the unsized tail of the struct, i.e. that a
+// pointer to the struct has the same metadata as a pointer to that field. This ensures that casting the struct by replacing the metadata of
+// a pointer to it is equivalent to casting that field.
+#[diagnostic::on_unimplemented(
+    message = "`{T}` is not the trailing unsized field of `{Self}`",
+    note = "DynDynBase can only be derived for structs whose last field is an unsized type implementing DynDynBase"
+)]
+pub trait DstWrapper<T: ?Sized> {}
+
+impl<
+        M: ?Sized,
+        T: ?Sized + Pointee<Metadata = DynMetadata<M>>,
+        W: ?Sized + Pointee<Metadata = DynMetadata<M>>,
+    > DstWrapper<T> for W
+{
+}
+
+/// # Safety
+///
+/// The provided metadata must be valid for the object that the pointer dereferences to.
+pub unsafe fn pointer_downcast_unchecked<P: DynDynPointer, D: ?Sized + Pointee>(
+    ptr: P,
+    metadata: <D as Pointee>::Metadata,
+) -> P::Rebind<D> {
+    let addr = &*ptr as *const P::Target as *const ();
+    let raw = P::into_raw(ptr) as *const ();
+
+    // DynDynPointer is safe to implement, so into_raw can't be trusted to return a pointer to the object that the metadata was retrieved
+    // from. If it doesn't, the pointer is leaked rather than being reconstructed with metadata that may not be valid for it.
+    assert!(
+        raw == addr,
+        "DynDynPointer::into_raw returned a pointer to a different object than the pointer dereferenced to"
+    );
+
+    // SAFETY: The pointer passed to from_raw was returned by into_raw and points to the object that the pointer dereferenced to, with its
+    //         metadata replaced by valid metadata for D, which is guaranteed by this fn's safety invariants.
+    unsafe { P::from_raw(ptr::from_raw_parts::<D>(raw, metadata)) }
+}
+
+// Used by #[derive(DynDyn)] to generate the implementations of GetDynDynTable and DowncastUnchecked for a type implementing DynDynPointer.
+// The derive expands to an invocation of this macro rather than generating the implementations itself, since the implementations need to
+// name Unsize and Pointee, which would otherwise require the crate using the derive to enable the corresponding unstable features.
+#[doc(hidden)]
+#[macro_export]
+#[allow_internal_unstable(ptr_metadata, unsize)]
+macro_rules! __dyn_dyn_impl_pointer {
+    ([$($params:tt)*] [$ty:ty] [$($preds:tt)*]) => {
+        // SAFETY: DynDynPointer requires StableDeref, so the results of Deref and DerefMut meet the stability requirements, and the table is
+        //         retrieved by dereferencing the pointer, which is correct by definition. The DowncastUnchecked implementation checks that the
+        //         raw pointer it reattaches the metadata to points to that same object.
+        unsafe impl<__dyn_dyn_B: ?Sized + $crate::DynDynBase, $($params)*> $crate::GetDynDynTable<__dyn_dyn_B> for $ty
+        where
+            $($preds)*
+            <$ty as ::core::ops::Deref>::Target: ::core::marker::Unsize<__dyn_dyn_B>,
+        {
+            type DynTarget = <$ty as ::core::ops::Deref>::Target;
+
+            fn get_dyn_dyn_table(&self) -> $crate::DynDynTable {
+                __dyn_dyn_B::get_dyn_dyn_table(&**self)
+            }
+        }
+
+        impl<'__dyn_dyn_a, $($params)*> $crate::DowncastUnchecked<'__dyn_dyn_a> for $ty
+        where
+            $($preds)*
+            Self: '__dyn_dyn_a,
+        {
+            type DowncastResult<__dyn_dyn_D: ?Sized + '__dyn_dyn_a> = <$ty as $crate::DynDynPointer>::Rebind<__dyn_dyn_D>;
+
+            unsafe fn downcast_unchecked<__dyn_dyn_D: ?Sized + ::core::ptr::Pointee>(
+                self,
+                metadata: <__dyn_dyn_D as ::core::ptr::Pointee>::Metadata,
+            ) -> <$ty as $crate::DynDynPointer>::Rebind<__dyn_dyn_D> {
+                // SAFETY: Invariants are passed through.
+                unsafe { $crate::internal::pointer_downcast_unchecked::<Self, __dyn_dyn_D>(self, metadata) }
+            }
+        }
+    };
+}
+
+pub use crate::__dyn_dyn_impl_pointer as impl_pointer;
+
+// Used by #[dyn_dyn_base] to only generate methods that deal with Box, Rc and Arc when the alloc feature of this crate is enabled, since the
+// generated code has no way of checking that itself.
+#[cfg(feature = "alloc")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __dyn_dyn_if_alloc {
+    ($($tt:tt)*) => { $($tt)* };
+}
+
+#[cfg(not(feature = "alloc"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __dyn_dyn_if_alloc {
+    ($($tt:tt)*) => {};
+}
+
+pub use crate::__dyn_dyn_if_alloc as if_alloc;
+
+#[cfg(feature = "alloc")]
+pub use alloc::{boxed::Box, rc::Rc, sync::Arc};
+
 #[allow(clippy::missing_safety_doc)] // This module is marked doc(hidden)
 pub unsafe trait DynDynImpl<B: ?Sized + DynDynBase> {
     fn get_dyn_dyn_table(&self) -> DynDynTable;
@@ -41,6 +249,10 @@ pub trait DerefHelperT: Sized {
         self
     }
 
+    fn __dyn_dyn_check_ref_mut_pin(self) -> Self {
+        self
+    }
+
     fn __dyn_dyn_check_ref_mut_dyn_dyn(self) -> Self {
         self
     }
@@ -99,8 +311,27 @@ impl<'a, B: ?Sized + DynDynBase, T: DynDyn<'a, B>> DerefHelper<B, T> {
     }
 }
 
-impl<'a, B: ?Sized + DynDynBase, T: DynDyn<'a, B> + StableDeref + DerefMut>
-    DerefHelper<B, &'a mut T>
+// A mutable reference to a pinned pointer is reborrowed as a pinned mutable reference rather than being dereferenced, so that the result of
+// the cast remains pinned. A shared reference to a pinned pointer is dereferenced like any other pointer, since a shared reference to the
+// object can't be used to move it anyway.
+impl<'a, B: ?Sized + DynDynBase, P: DerefMut> DerefHelper<B, &'a mut Pin<P>>
+where
+    P::Target: Unsize<B> + 'a,
+{
+    pub fn __dyn_dyn_check_ref_mut_pin(
+        self,
+    ) -> DerefHelperResolved<
+        'a,
+        B,
+        Pin<&'a mut P::Target>,
+        Pin<&'a mut P::Target>,
+        impl FnOnce(Pin<&'a mut P::Target>) -> Pin<&'a mut P::Target>,
+    > {
+        DerefHelperResolved(self.0.as_mut(), |x| x, self.1, PhantomData)
+    }
+}
+
+impl<'a, B: ?Sized + DynDynBase, T: GetDynDynTable<B> + DerefMut> DerefHelper<B, &'a mut T>
 where
     T::Target: Unsize<B>,
 {
@@ -122,7 +353,7 @@ where
     }
 }
 
-impl<'a, B: ?Sized + DynDynBase, T: DynDyn<'a, B> + StableDeref> DerefHelper<B, &'a T>
+impl<'a, B: ?Sized + DynDynBase, T: GetDynDynTable<B> + Deref> DerefHelper<B, &'a T>
 where
     T::Target: Unsize<B>,
 {
@@ -201,6 +432,29 @@ impl<'a, B: ?Sized + DynDynBase, T: DynDyn<'a, B>> DerefHelperEnd<'a, B> for Der
     }
 }
 
+// Used by chained casts (e.g. dyn_dyn_cast!(A => B => C, ...)) to retrieve the table of an intermediate base trait without consuming the
+// pointer, so that the original pointer can still be returned if a later lookup fails.
+pub trait DerefHelperChain<'a, B: ?Sized + DynDynBase>: DerefHelperEnd<'a, B> {
+    unsafe fn get_chained_dyn_dyn_table<
+        B2: ?Sized + DynDynBase + Pointee<Metadata = DynMetadata<B2>>,
+    >(
+        &self,
+        metadata: DynMetadata<B2>,
+    ) -> DynDynTable;
+}
+
+// As with the DerefHelperEnd impl above, this impl only exists to get a friendlier error message.
+impl<'a, B: ?Sized + DynDynBase, T: DynDyn<'a, B>> DerefHelperChain<'a, B> for DerefHelper<B, T> {
+    unsafe fn get_chained_dyn_dyn_table<
+        B2: ?Sized + DynDynBase + Pointee<Metadata = DynMetadata<B2>>,
+    >(
+        &self,
+        _: DynMetadata<B2>,
+    ) -> DynDynTable {
+        unreachable!()
+    }
+}
+
 pub struct DerefHelperResolved<'a, B: ?Sized + DynDynBase, T: DynDyn<'a, B>, E, F: FnOnce(T) -> E>(
     T,
     F,
@@ -239,3 +493,23 @@ impl<'a, B: ?Sized + DynDynBase, T: DynDyn<'a, B>, E, F: FnOnce(T) -> E> DerefHe
         self.1(self.0)
     }
 }
+
+impl<'a, B: ?Sized + DynDynBase, T: DynDyn<'a, B> + Deref, E, F: FnOnce(T) -> E>
+    DerefHelperChain<'a, B> for DerefHelperResolved<'a, B, T, E, F>
+where
+    T::Target: Unsize<B>,
+{
+    unsafe fn get_chained_dyn_dyn_table<
+        B2: ?Sized + DynDynBase + Pointee<Metadata = DynMetadata<B2>>,
+    >(
+        &self,
+        metadata: DynMetadata<B2>,
+    ) -> DynDynTable {
+        let data = &*self.0 as *const T::Target as *const ();
+
+        // SAFETY: By the safety invariants of GetDynDynTable<B>, dereferencing the pointer yields the same object that the table was
+        //         retrieved from. The caller guarantees that the provided metadata was retrieved from that table, so attaching it to the
+        //         data pointer is valid. The resulting reference does not outlive the borrow of self.
+        B2::get_dyn_dyn_table(unsafe { &*ptr::from_raw_parts::<B2>(data, metadata) })
+    }
+}
@@ -4,18 +4,34 @@
 #![warn(clippy::undocumented_unsafe_blocks)]
 #![allow(clippy::needless_borrowed_reference)]
 #![forbid(unsafe_op_in_unsafe_fn)]
+#![allow(internal_features)] // allow_internal_unstable is needed by the expansion of #[derive(DynDyn)]
+#![feature(allow_internal_unstable)]
 #![feature(coerce_unsized)]
 #![cfg_attr(feature = "dynamic-names", feature(const_type_name))]
 #![feature(doc_cfg)]
+#![cfg_attr(feature = "allocator-api", feature(allocator_api))]
+#![cfg_attr(all(feature = "alloc", not(feature = "std")), feature(core_intrinsics))]
+#![cfg_attr(feature = "std", feature(mapped_lock_guards))]
 #![feature(ptr_metadata)]
 #![feature(unsize)]
 
 #[cfg(feature = "alloc")]
 extern crate alloc;
 
+#[cfg(feature = "allocator-api")]
+mod alloc_api;
 mod cast_target;
+mod error;
 mod fat;
+mod guard;
+mod inline;
+mod pointer;
+mod raw;
 mod table;
+#[cfg(feature = "alloc")]
+mod thin;
+mod view;
+mod wrapper;
 
 #[doc(hidden)]
 pub mod internal;
@@ -24,6 +40,37 @@ pub mod internal;
 ///
 /// This macro marks a trait as being a base for dynamic trait object downcasting. All `impl` blocks for this trait will need to use the
 /// [`#[dyn_dyn_impl]`](dyn_dyn_impl) attribute to declare what traits they wish to expose.
+///
+/// Base traits may have lifetime parameters, e.g. `trait Node<'src>`. When casting from such a trait using [`dyn_dyn_cast!`], the lifetime
+/// can either be named explicitly or elided, e.g. `dyn_dyn_cast!(Node<'_> => Trait, r)`.
+///
+/// This macro also generates the inherent methods `dyn_dyn_cast_ref`, `dyn_dyn_cast_mut` and `dyn_dyn_implements` on the base trait object
+/// type, as well as `dyn_dyn_cast_box`, `dyn_dyn_cast_rc` and `dyn_dyn_cast_arc` if the `alloc` feature is enabled. They are prefixed so
+/// that they don't shadow methods of the same name on the base trait or its supertraits, since inherent methods take precedence over trait
+/// methods. Since these methods cannot tie the lifetime of the resulting trait object to that of the base trait object, they are only
+/// available on base trait objects with a `'static` object lifetime bound, e.g. `dyn Base + 'static`.
+///
+/// # Examples
+///
+/// ```rust
+/// # use dyn_dyn::{dyn_dyn_base, dyn_dyn_impl};
+/// #[dyn_dyn_base]
+/// trait Base {}
+/// trait Trait {}
+///
+/// struct Struct;
+///
+/// #[dyn_dyn_impl(Trait)]
+/// impl Base for Struct {}
+/// impl Trait for Struct {}
+///
+/// let r: &dyn Base = &Struct;
+///
+/// assert!(r.dyn_dyn_implements::<dyn Trait>());
+/// assert!(r.dyn_dyn_cast_ref::<dyn Trait>().is_some());
+/// # #[cfg(feature = "alloc")]
+/// assert!((Box::new(Struct) as Box<dyn Base>).dyn_dyn_cast_box::<dyn Trait>().is_ok());
+/// ```
 pub use dyn_dyn_macros::dyn_dyn_base;
 
 /// Performs a dynamic downcast of a reference to a trait object where the trait was declared with [`#[dyn_dyn_base]`](dyn_dyn_base).
@@ -36,11 +83,54 @@ pub use dyn_dyn_macros::dyn_dyn_base;
 ///
 /// - A (mutable) reference to a type that implements `B`, returning a (mutable) reference referring to the same object as the original
 ///   reference
+/// - A mutable reference to a [`Pin<P>`](Pin), where `P` dereferences to a type that implements `B`, returning a pinned mutable reference
+///   referring to the pointee of that pointer
 /// - A (mutable) reference to a pointer type that implements [`DynDyn<B>`], returning a (mutable) reference referring to the pointee of
 ///   that pointer
 /// - A (mutable) reference to a pointer type that implements Deref with a target that implements `B`, returning a (mutable) reference
 ///   referring to the pointee of that pointer
 ///
+/// When `move` is used, the pointer is consumed and the result has the same pointer type as the original pointer. This includes pinned
+/// pointers such as `Pin<&mut dyn B>` and `Pin<Box<dyn B>>`, which are cast without being unpinned, and the `Weak` pointers of `Rc` and
+/// `Arc`, which are temporarily upgraded to retrieve the table. Casting a `Weak` pointer whose object has already been dropped always
+/// fails, since its table is empty. If the `Weak` pointer is wrapped in a [`DynDynFat`] that cached the table while the object was still
+/// live, the cast uses the cached table instead and succeeds, producing a `Weak` pointer that can't be upgraded.
+///
+/// Guards such as `Ref<dyn B>`, `RefMut<dyn B>`, `MutexGuard<dyn B>`, `RwLockReadGuard<dyn B>` and `RwLockWriteGuard<dyn B>` can also be
+/// cast with `move`, producing a guard over the target trait object using the guard's `map` function. Since the standard library's lock
+/// guards can only be mapped into `MappedMutexGuard` and similar types, those are returned when casting lock guards.
+///
+/// The target trait may include auto traits, e.g. `dyn_dyn_cast!(Base => Trait + Send, r)`. Since the metadata for `dyn Trait + Send` is not
+/// guaranteed to match that of `dyn Trait` (see [rust-lang/rust#127323](https://github.com/rust-lang/rust/issues/127323)), such a cast will
+/// only succeed if the concrete type explicitly exposes `Trait + Send` via [`#[dyn_dyn_impl]`](dyn_dyn_impl), e.g. using
+/// `#[dyn_dyn_impl(Trait, Trait + Send)]`. For the same reason, auto traits are never propagated from the pointer being cast: casting a
+/// `Box<dyn Base + Send>` to `dyn Trait + Send` does not fall back to the metadata for `dyn Trait`, even though the object is known to be
+/// `Send`, and Rust provides no coercion from `dyn Trait` to `dyn Trait + Send` that could be used instead. Types that need to be cast to
+/// targets with auto traits must expose each such target explicitly.
+///
+/// If the target trait was itself declared with [`#[dyn_dyn_base]`](dyn_dyn_base), then the result can be cast further. A chain of such
+/// casts can also be written as a single expression, e.g. `dyn_dyn_cast!(BaseA => BaseB => Trait, r)`, which will return the original
+/// pointer if any of the lookups along the chain fail. Chained casts require that the pointer being cast implements [`Deref`].
+///
+/// Multiple targets can be requested at once by listing them in parentheses, e.g. `dyn_dyn_cast!(Base => (TraitA, TraitB), r)`. This looks
+/// up all of the targets in a single pass over the object's table and returns a tuple containing a pointer for each target if all of them
+/// were found, or the original pointer otherwise. Since each target needs its own pointer, this requires that the pointer being cast
+/// implements `CloneStableDeref`, so it can be used with shared references or with `move` on pointers like `Rc` and `Arc`, but not with
+/// `mut`.
+///
+/// The base trait object can also be the unsized last field of a struct that implements [`DynDynBase`] by passing its table through, such
+/// as one using [`#[derive(DynDynBase)]`](derive@DynDynBase). The struct is written in brackets after the target with `$` in place of the
+/// trait object, e.g. `dyn_dyn_cast!(move Base => Trait [Envelope<$>], rc)` to cast an `Rc<Envelope<dyn Base>>` into an
+/// `Rc<Envelope<dyn Trait>>`. This crate provides such implementations for the standard library wrappers `Cell` and `ManuallyDrop`, e.g.
+/// `dyn_dyn_cast!(move Base => Trait [Cell<$>], rc)` for an `Rc<Cell<dyn Base>>`. There are no implementations for `UnsafeCell`,
+/// `RefCell`, `Mutex` or `RwLock`, which allow the object to be mutably borrowed through a shared reference to the wrapper. The table can
+/// only be retrieved by calling a method on the wrapped object through a reference, so these would need to either borrow or lock the
+/// object, which would panic or deadlock if it's already borrowed mutably or locked, or risk aliasing a live mutable reference to it.
+///
+/// By default, a failed cast returns the original pointer. If the `try` keyword is placed at the start of the macro input, e.g.
+/// `dyn_dyn_cast!(try Base => Trait, r)`, then a failed cast instead returns a [`DynDynCastError`] wrapping the original pointer and
+/// describing why the cast failed.
+///
 /// # Examples
 ///
 /// ```rust
@@ -75,13 +165,169 @@ pub use dyn_dyn_macros::dyn_dyn_base;
 /// # #[cfg(feature = "alloc")]
 /// assert!(downcast_box(Box::new(s)).is_ok());
 /// ```
+///
+/// ```rust
+/// # use dyn_dyn::{dyn_dyn_base, dyn_dyn_cast, dyn_dyn_impl};
+/// #[dyn_dyn_base]
+/// trait BaseA {}
+/// #[dyn_dyn_base]
+/// trait BaseB {}
+/// trait Trait {}
+///
+/// struct Struct;
+///
+/// #[dyn_dyn_impl(BaseB)]
+/// impl BaseA for Struct {}
+/// #[dyn_dyn_impl(Trait)]
+/// impl BaseB for Struct {}
+/// impl Trait for Struct {}
+///
+/// fn downcast(r: &dyn BaseA) -> Result<&dyn Trait, &dyn BaseA> {
+///     dyn_dyn_cast!(BaseA => BaseB => Trait, r)
+/// }
+///
+/// assert!(downcast(&Struct).is_ok());
+/// ```
+///
+/// ```rust
+/// # use dyn_dyn::{dyn_dyn_base, dyn_dyn_cast, dyn_dyn_impl};
+/// # use core::fmt::{self, Debug, Display};
+/// #[dyn_dyn_base]
+/// trait Base {}
+///
+/// #[derive(Debug)]
+/// struct Struct;
+///
+/// #[dyn_dyn_impl(Debug, Display)]
+/// impl Base for Struct {}
+/// impl Display for Struct {
+///     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+///         write!(f, "Struct")
+///     }
+/// }
+///
+/// fn views(r: &dyn Base) -> Option<(&dyn Debug, &dyn Display)> {
+///     dyn_dyn_cast!(Base => (Debug, Display), r).ok()
+/// }
+///
+/// assert!(views(&Struct).is_some());
+/// ```
+///
+/// ```rust
+/// # use dyn_dyn::{dyn_dyn_base, dyn_dyn_cast, dyn_dyn_impl, DynDynCastError};
+/// #[dyn_dyn_base]
+/// trait Base {}
+/// trait Trait {}
+///
+/// struct Struct;
+///
+/// #[dyn_dyn_impl]
+/// impl Base for Struct {}
+///
+/// fn downcast(r: &dyn Base) -> Result<&dyn Trait, DynDynCastError<&dyn Base>> {
+///     dyn_dyn_cast!(try Base => Trait, r)
+/// }
+///
+/// let err = downcast(&Struct).err().unwrap();
+///
+/// assert_eq!(core::any::TypeId::of::<dyn Trait>(), err.target_type_id());
+/// ```
 pub use dyn_dyn_macros::dyn_dyn_cast;
 
+/// Performs a dynamic downcast of a reference to a trait object where the trait was declared with [`#[dyn_dyn_base]`](dyn_dyn_base) into a
+/// reference to its concrete type.
+///
+/// This macro accepts the same inputs and modes as [`dyn_dyn_cast!`], but instead of casting to another trait object it checks whether
+/// the concrete type of the referenced object is exactly the requested type. Only `'static` types can be used as the target of a
+/// downcast. As with [`dyn_dyn_cast!`], the `try` keyword can be used to return a [`DynDynCastError`] on failure.
+///
+/// # Examples
+///
+/// ```rust
+/// # use dyn_dyn::{dyn_dyn_base, dyn_dyn_downcast, dyn_dyn_impl};
+/// #[dyn_dyn_base]
+/// trait Base {}
+///
+/// struct StructA(u32);
+/// struct StructB;
+///
+/// #[dyn_dyn_impl]
+/// impl Base for StructA {}
+///
+/// #[dyn_dyn_impl]
+/// impl Base for StructB {}
+///
+/// fn downcast(r: &dyn Base) -> Result<&StructA, &dyn Base> {
+///     dyn_dyn_downcast!(Base => StructA, r)
+/// }
+///
+/// # #[cfg(feature = "alloc")]
+/// fn downcast_box(r: Box<dyn Base>) -> Result<Box<StructA>, Box<dyn Base>> {
+///     dyn_dyn_downcast!(move Base => StructA, r)
+/// }
+///
+/// assert_eq!(Some(1), downcast(&StructA(1)).ok().map(|s| s.0));
+/// assert!(downcast(&StructB).is_err());
+/// # #[cfg(feature = "alloc")]
+/// assert!(downcast_box(Box::new(StructA(1))).is_ok());
+/// ```
+pub use dyn_dyn_macros::dyn_dyn_downcast;
+
+/// Dispatches on which of several traits the object referenced by a pointer to a base trait object implements.
+///
+/// This macro takes the same modes and base trait as [`dyn_dyn_cast!`], followed by an expression and a list of arms. Each arm names a
+/// target trait and a pattern that the cast pointer is bound to, and the arms are tried in order using a single lookup of the object's
+/// [`DynDynTable`]. The body of the first arm whose target the object can be cast to is evaluated. The last arm must be a wildcard or
+/// identifier pattern, which is bound to the original pointer if none of the other arms match.
+///
+/// Since the arms are not wrapped in a closure, `return`, `?` and `break` can be used in their bodies as they would be in a regular
+/// `match` expression.
+///
+/// # Examples
+///
+/// ```rust
+/// # use dyn_dyn::{dyn_dyn_base, dyn_dyn_impl, dyn_dyn_match};
+/// # use core::fmt::{Debug, Display};
+/// #[dyn_dyn_base]
+/// trait Base {}
+///
+/// #[dyn_dyn_impl(Display, Debug)]
+/// impl Base for u32 {}
+///
+/// #[dyn_dyn_impl(Debug)]
+/// impl Base for () {}
+///
+/// fn describe(r: &dyn Base) -> String {
+///     dyn_dyn_match!(Base, r {
+///         Display(d) => format!("display {}", d),
+///         Debug(d) => format!("debug {:?}", d),
+///         _ => "unknown".to_owned(),
+///     })
+/// }
+///
+/// assert_eq!("display 1", describe(&1_u32));
+/// assert_eq!("debug ()", describe(&()));
+/// ```
+pub use dyn_dyn_macros::dyn_dyn_match;
+
 /// Marks an `impl` block as targeting a trait that was declared with the [`#[dyn_dyn_base]`](dyn_dyn_base) attribute.
 ///
 /// This attribute allows the `impl` block to specify what other traits should be exposed for downcasting via the base trait that's being
 /// implemented in this block.
 ///
+/// The implementing type may have lifetime parameters or be a reference with an elided lifetime, e.g. `&str`, in which case every exposed
+/// trait must be implemented for all choices of those lifetimes. Such types cannot be downcast into using [`dyn_dyn_downcast!`].
+///
+/// If the `impl` block has type parameters, the implementing type can only be downcast into if each of them is bound by `'static`, either
+/// in its own bounds or in the `where` clause, e.g. `impl<T: 'static> Base for Wrapper<T>`. Exposing any traits requires such a bound
+/// anyway. An `impl` block that exposes no traits can leave it out, in which case its table records no concrete type at all.
+///
+/// Exposed traits may also have lifetime arguments, as long as each of those lifetimes is either `'static`, one of the lifetime arguments
+/// of the base trait being implemented or bound by a `for<...>` binder, e.g. `for<'x> Matches<'x>`. When casting to such a trait, the
+/// lifetimes of the target trait must likewise be named in terms of the lifetime arguments of the base trait, which ensures that the result
+/// cannot be used to extend any lifetimes. Elided reference lifetimes in exposed traits, e.g. `Iterator<Item = &str>`, are taken to be
+/// `'static`, except within `Fn(...)` sugar and `fn(...)` pointer types, where they are higher-ranked as usual.
+///
 /// # Examples
 ///
 /// ```rust
@@ -98,6 +344,37 @@ pub use dyn_dyn_macros::dyn_dyn_cast;
 /// ```
 ///
 /// ```rust
+/// # use core::fmt::Debug;
+/// # use dyn_dyn::{dyn_dyn_base, dyn_dyn_impl};
+/// #[dyn_dyn_base]
+/// trait Base {}
+///
+/// #[derive(Debug)]
+/// struct Struct;
+///
+/// // Auto traits must be exposed explicitly to be able to cast to them
+/// #[dyn_dyn_impl(Debug, Debug + Send, Debug + Send + Sync)]
+/// impl Base for Struct {}
+/// ```
+///
+/// ```rust
+/// # use dyn_dyn::{dyn_dyn_base, dyn_dyn_cast, dyn_dyn_impl};
+/// #[dyn_dyn_base]
+/// trait Deserializer<'de> {}
+/// trait Visitor<'de> {}
+///
+/// struct Struct<'de>(&'de str);
+///
+/// #[dyn_dyn_impl(Visitor<'de>)]
+/// impl<'de> Deserializer<'de> for Struct<'de> {}
+/// impl<'de> Visitor<'de> for Struct<'de> {}
+///
+/// fn visitor<'a, 'de>(d: &'a dyn Deserializer<'de>) -> Option<&'a dyn Visitor<'de>> {
+///     dyn_dyn_cast!(Deserializer<'de> => Visitor<'de>, d).ok()
+/// }
+/// ```
+///
+/// ```rust
 /// # use dyn_dyn::{dyn_dyn_base, dyn_dyn_impl};
 /// #[dyn_dyn_base]
 /// trait Base {}
@@ -112,29 +389,139 @@ pub use dyn_dyn_macros::dyn_dyn_cast;
 /// ```
 pub use dyn_dyn_macros::dyn_dyn_impl;
 
+/// Derives [`DynDynBase`] for a struct whose last field is an unsized type implementing [`DynDynBase`], such as a base trait object.
+///
+/// The generated implementation retrieves the [`DynDynTable`] from the last field, allowing pointers to the struct to be cast using the
+/// outer struct syntax of [`dyn_dyn_cast!`], e.g. `dyn_dyn_cast!(Base => Trait [Envelope<$>], r)`. It only applies when the last field is
+/// the unsized tail of the struct, which is checked at compile time. Since the concrete type recorded in that table is the type of the field
+/// rather than of the struct, it is removed from the returned table, so the struct can't be downcast into using [`dyn_dyn_downcast!`].
+///
+/// # Examples
+///
+/// ```rust
+/// # use dyn_dyn::{dyn_dyn_base, dyn_dyn_cast, dyn_dyn_impl, DynDynBase};
+/// #[dyn_dyn_base]
+/// trait Payload {}
+/// trait Text {
+///     fn text(&self) -> &str;
+/// }
+///
+/// #[derive(DynDynBase)]
+/// struct Envelope<P: ?Sized> {
+///     id: u32,
+///     payload: P,
+/// }
+///
+/// struct Message(&'static str);
+///
+/// #[dyn_dyn_impl(Text)]
+/// impl Payload for Message {}
+/// impl Text for Message {
+///     fn text(&self) -> &str {
+///         self.0
+///     }
+/// }
+///
+/// let envelope: &Envelope<dyn Payload> = &Envelope { id: 1, payload: Message("hello") };
+/// let envelope: &Envelope<dyn Text> = dyn_dyn_cast!(Payload => Text [Envelope<$>], envelope).ok().unwrap();
+///
+/// assert_eq!(1, envelope.id);
+/// assert_eq!("hello", envelope.payload.text());
+/// ```
+pub use dyn_dyn_macros::DynDynBase;
+
+/// Derives [`GetDynDynTable`] and [`DowncastUnchecked`] for a custom smart pointer implementing [`DynDynPointer`], making it possible to
+/// cast it using [`dyn_dyn_cast!`].
+///
+/// Since [`DynDynPointer`] requires `StableDeref`, the pointer can also be cast into a reference without being moved. The derive doesn't
+/// implement `StableDeref` itself, so pointer types that already implement it can use the derive as well.
+///
+/// # Examples
+///
+/// ```rust
+/// # use core::ops::Deref;
+/// # use dyn_dyn::{dyn_dyn_base, dyn_dyn_cast, dyn_dyn_impl, DynDyn, DynDynPointer};
+/// # use stable_deref_trait::StableDeref;
+/// #[derive(DynDyn)]
+/// struct Obj<T: ?Sized>(Box<T>);
+///
+/// impl<T: ?Sized> Deref for Obj<T> {
+///     type Target = T;
+///
+///     fn deref(&self) -> &T {
+///         &self.0
+///     }
+/// }
+///
+/// // SAFETY: Obj<T> is a simple wrapper around Box<T>.
+/// unsafe impl<T: ?Sized> StableDeref for Obj<T> {}
+///
+/// impl<T: ?Sized> DynDynPointer for Obj<T> {
+///     type Rebind<D: ?Sized> = Obj<D>;
+///
+///     fn into_raw(this: Self) -> *const T {
+///         Box::into_raw(this.0)
+///     }
+///
+///     unsafe fn from_raw<D: ?Sized>(ptr: *const D) -> Obj<D> {
+///         // SAFETY: The pointer came from Box::into_raw and still refers to the same object.
+///         Obj(unsafe { Box::from_raw(ptr as *mut D) })
+///     }
+/// }
+///
+/// #[dyn_dyn_base]
+/// trait Base {}
+/// trait Trait {}
+///
+/// struct Struct;
+///
+/// #[dyn_dyn_impl(Trait)]
+/// impl Base for Struct {}
+/// impl Trait for Struct {}
+///
+/// let obj: Obj<dyn Base> = Obj(Box::new(Struct));
+///
+/// assert!(dyn_dyn_cast!(Base => Trait, &obj).is_ok());
+/// assert!(dyn_dyn_cast!(move Base => Trait, obj).is_ok());
+/// ```
+pub use dyn_dyn_macros::DynDyn;
+
 pub use cast_target::DynDynCastTarget;
+pub use error::DynDynCastError;
 pub use fat::DynDynFat;
+pub use inline::DynDynInline;
+pub use pointer::DynDynPointer;
+pub use raw::DynDynRawPtr;
 pub use table::{AnyDynMetadata, DynDynTable, DynDynTableEntry, DynDynTableIterator};
-
-#[cfg(doc)]
-use core::ops::Deref;
+#[cfg(feature = "alloc")]
+pub use thin::{DynDynThinArc, DynDynThinBox};
+pub use view::DynDynView;
 
 use cfg_if::cfg_if;
 use core::marker::{PhantomData, Unsize};
-use core::ops::DerefMut;
-use core::ptr::{self, Pointee};
-use stable_deref_trait::StableDeref;
+use core::ops::{Deref, DerefMut};
+use core::pin::Pin;
+use core::ptr::{self, DynMetadata, Pointee};
+use stable_deref_trait::{CloneStableDeref, StableDeref};
 
 /// A type that can be dynamically downcast to other traits using the [`dyn_dyn_cast!`] macro.
 ///
 /// This trait should not be manually implemented by user code. Instead, this trait should be implemented by using the
-/// [`#[dyn_dyn_base]`](dyn_dyn_base) attribute on the trait in question. The exact shape of this trait is subject to change at any time, so
+/// [`#[dyn_dyn_base]`](dyn_dyn_base) attribute on the trait in question, or for structs wrapping a base trait object, by using
+/// [`#[derive(DynDynBase)]`](derive@DynDynBase). The exact shape of this trait is subject to change at any time, so
 /// it generally shouldn't be relied upon in external code except as a trait bound.
 ///
 /// # Safety
 ///
 /// The result of calling [`DynDynBase::get_dyn_dyn_table`] on an object through a given base must never change for the lifetime of that
 /// object, even if the object itself is mutated.
+///
+/// The concrete type recorded in the returned table must be the concrete type of the object, since [`dyn_dyn_downcast!`] and
+/// [`DynDynTable::is`] rely on it to downcast the object to that type. If the concrete type has lifetime parameters, the table must instead
+/// record that type with its lifetimes replaced by `'static` and be marked as lifetime-erased, so that it can't be downcast into. The table
+/// may also record no concrete type at all, in which case it can never be downcast into. Implementations for structs wrapping a base trait
+/// object that pass through the table of the object they wrap must remove its concrete type using [`DynDynTable::without_concrete_type`],
+/// since it is not the concrete type of the wrapper.
 pub unsafe trait DynDynBase {
     /// Gets the [`DynDynTable`] for this object, for traits exposed via this base trait.
     ///
@@ -143,17 +530,59 @@ pub unsafe trait DynDynBase {
     fn get_dyn_dyn_table(&self) -> DynDynTable;
 }
 
+/// A base trait object declared with [`#[dyn_dyn_base]`](dyn_dyn_base), e.g. `dyn Base`.
+///
+/// Unlike [`DynDynBase`], this is not implemented for wrappers around a base trait object such as `Cell<dyn Base>` or structs using
+/// [`#[derive(DynDynBase)]`](derive@DynDynBase). The table of such a wrapper describes its unsized last field rather than the wrapper
+/// itself, so APIs that attach metadata from the table to a pointer to the base without wrapping the target in the same struct, such as
+/// [`cast`], require this trait instead. This trait is sealed and is implemented automatically by [`#[dyn_dyn_base]`](dyn_dyn_base).
+pub trait DynDynBaseObject:
+    DynDynBase + Pointee<Metadata = DynMetadata<Self>> + internal::BaseTraitObject
+{
+}
+
+impl<B: ?Sized + internal::BaseTraitObject> DynDynBaseObject for B {}
+
+/// Gets the [`DynDynTable`] of an object via the base trait `B`, which lists the traits that the object can be cast to.
+///
+/// # Examples
+///
+/// ```rust
+/// # use dyn_dyn::{dyn_dyn_base, dyn_dyn_impl};
+/// # use core::fmt::{Debug, Display};
+/// #[dyn_dyn_base]
+/// trait Base {}
+///
+/// #[derive(Debug)]
+/// struct Struct;
+///
+/// #[dyn_dyn_impl(Debug, Display)]
+/// impl Base for Struct {}
+/// # impl Display for Struct {
+/// #     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result { Ok(()) }
+/// # }
+///
+/// let table = dyn_dyn::introspect::<dyn Base>(&Struct);
+/// let names: Vec<_> = table.into_iter().map(|entry| entry.name()).collect();
+///
+/// assert_eq!(vec!["dyn Debug", "dyn Display"], names);
+/// assert!(table.can_cast::<dyn Debug>());
+/// ```
+pub fn introspect<B: ?Sized + DynDynBase>(obj: &B) -> DynDynTable {
+    obj.get_dyn_dyn_table()
+}
+
 /// Wraps a reference to a pointer implementing [`GetDynDynTable<B>`] and which can be dereferenced to perform the downcast.
 ///
 /// Using [`dyn_dyn_cast!`] on this struct will call [`GetDynDynTable::get_dyn_dyn_table`] on the pointer itself, then dereference this
-/// pointer to perform the downcast. This allows a pointer implementing [`DynDyn<B>`] to be downcast into a reference without moving the
-/// pointer itself.
-pub struct DynDynRef<'a, B: ?Sized + DynDynBase, T: GetDynDynTable<B> + StableDeref>(
+/// pointer to perform the downcast. This allows a pointer implementing [`GetDynDynTable<B>`] to be downcast into a reference without moving
+/// the pointer itself.
+pub struct DynDynRef<'a, B: ?Sized + DynDynBase, T: GetDynDynTable<B> + Deref>(
     &'a T,
     PhantomData<fn(B) -> B>,
 );
 
-impl<'a, B: ?Sized + DynDynBase, T: GetDynDynTable<B> + StableDeref> DynDynRef<'a, B, T>
+impl<'a, B: ?Sized + DynDynBase, T: GetDynDynTable<B> + Deref> DynDynRef<'a, B, T>
 where
     T::Target: Unsize<B>,
 {
@@ -163,19 +592,25 @@ where
     }
 }
 
+impl<'a, B: ?Sized + DynDynBase, T: GetDynDynTable<B> + Deref> Clone for DynDynRef<'a, B, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, B: ?Sized + DynDynBase, T: GetDynDynTable<B> + Deref> Copy for DynDynRef<'a, B, T> {}
+
 /// Wraps a mutable reference to a pointer implementing [`GetDynDynTable<B>`] and which can be dereferenced to perform the downcast.
 ///
 /// Using [`dyn_dyn_cast!`] on this struct will call [`GetDynDynTable::get_dyn_dyn_table`] on the pointer itself, then dereference this
-/// pointer to perform the downcast. This allows a pointer implementing [`DynDyn<B>`] to be downcast into a mutable reference without moving
-/// the pointer itself.
-pub struct DynDynRefMut<'a, B: ?Sized + DynDynBase, T: GetDynDynTable<B> + StableDeref + DerefMut>(
+/// pointer to perform the downcast. This allows a pointer implementing [`GetDynDynTable<B>`] to be downcast into a mutable reference without
+/// moving the pointer itself.
+pub struct DynDynRefMut<'a, B: ?Sized + DynDynBase, T: GetDynDynTable<B> + DerefMut>(
     &'a mut T,
     PhantomData<fn(B) -> B>,
 );
 
-impl<'a, B: ?Sized + DynDynBase, T: GetDynDynTable<B> + StableDeref + DerefMut>
-    DynDynRefMut<'a, B, T>
-{
+impl<'a, B: ?Sized + DynDynBase, T: GetDynDynTable<B> + DerefMut> DynDynRefMut<'a, B, T> {
     /// Creates a new [`DynDynRefMut`] for the provided mutable reference to a pointer.
     pub fn new(r: &'a mut T) -> Self {
         DynDynRefMut(r, PhantomData)
@@ -186,11 +621,12 @@ impl<'a, B: ?Sized + DynDynBase, T: GetDynDynTable<B> + StableDeref + DerefMut>
 ///
 /// # Safety
 ///
-/// - If this type implements [`Deref`], then the reference returned by calling [`Deref::deref`] must not change for the lifetime of this
-///   pointer unless the pointer itself is mutated.
-/// - If this type implements [`DerefMut`], then the reference returned by calling [`DerefMut::deref_mut`] must not change for the lifetime
-///   of this pointer unless the pointer itself is mutated and must point to the same object as a reference returned by calling
-///   [`Deref::deref`], including having identical metadata. Additionally, calling [`DerefMut::deref_mut`] must not mutate the pointer.
+/// - If this type implements [`Deref`], then the reference returned by calling [`Deref::deref`] must not change for as long as the pointer
+///   itself is neither mutated nor moved. A container that stores its object inline, such as [`DynDynInline`], moves the object along with
+///   it and so may return a different address after being moved, but must still refer to the same object with identical metadata.
+/// - If this type implements [`DerefMut`], then the reference returned by calling [`DerefMut::deref_mut`] must not change for as long as the
+///   pointer itself is neither mutated nor moved and must point to the same object as a reference returned by calling [`Deref::deref`],
+///   including having identical metadata. Additionally, calling [`DerefMut::deref_mut`] must not mutate the pointer.
 /// - If this type implements [`Deref`], then the reference returned by calling [`Deref::deref`] must be unsize-coercible to a reference to
 ///   [`GetDynDynTable::get_dyn_dyn_table`].
 /// - If this type implements [`Deref`], then the returned table must be equivalent to calling [`GetDynDynTable::get_dyn_dyn_table`] on a
@@ -199,8 +635,12 @@ impl<'a, B: ?Sized + DynDynBase, T: GetDynDynTable<B> + StableDeref + DerefMut>
 ///   metadata retrieved from the table returned by calling [`GetDynDynTable::get_dyn_dyn_table`] on this pointer shall be valid and safe to
 ///   use.
 pub unsafe trait GetDynDynTable<B: ?Sized + DynDynBase> {
-    /// The actual type that this pointer currently points to. This type is used to allow propagation of auto trait bounds such as `Send`
-    /// and `Sync` in the `dyn_dyn_cast!` macro.
+    /// The actual type that this pointer currently points to, including any auto traits such as `Send` and `Sync`, e.g. `dyn Base + Send`.
+    ///
+    /// Note that [`dyn_dyn_cast!`] does not use this to propagate auto traits to the target trait object, since that would require
+    /// assuming that the metadata for `dyn Trait + Send` matches that of `dyn Trait` (see
+    /// [rust-lang/rust#127323](https://github.com/rust-lang/rust/issues/127323)). Targets with auto traits must instead be explicitly exposed
+    /// by the concrete type.
     type DynTarget: ?Sized + Unsize<B>;
 
     /// Gets the [`DynDynTable`] for the object that this pointer points to.
@@ -237,6 +677,140 @@ pub trait DynDyn<'a, B: ?Sized + DynDynBase>: GetDynDynTable<B> + DowncastUnchec
 
 impl<'a, B: ?Sized + DynDynBase, T: GetDynDynTable<B> + DowncastUnchecked<'a>> DynDyn<'a, B> for T {}
 
+/// Performs a dynamic downcast of a pointer implementing [`DynDyn<B>`] to a pointer to the trait object `D`.
+///
+/// This is a function-based equivalent of the [`dyn_dyn_cast!`] macro that can be used from generic code where the base trait and target
+/// trait are type parameters rather than paths known at the call site. On success, the downcast pointer is returned. Otherwise, the original
+/// pointer is returned unchanged.
+///
+/// Unlike [`dyn_dyn_cast!`], this function does not automatically dereference its input, so pointers implementing [`DynDyn<B>`] that
+/// should be downcast without being moved must first be wrapped in [`DynDynRef`] or [`DynDynRefMut`]. Since the lifetime of the result
+/// cannot be tied to the lifetime of the base trait object in generic code, both `B` and `D` must be `'static`. `B` must also be a base
+/// trait object itself rather than a wrapper around one, since the metadata for `D` is attached directly to the pointer to `B`.
+///
+/// # Examples
+///
+/// ```rust
+/// # use core::fmt::Debug;
+/// # use dyn_dyn::{dyn_dyn_base, dyn_dyn_impl, DynDyn, DynDynBaseObject};
+/// fn count_debug<'a, B, P>(ptrs: impl IntoIterator<Item = P>) -> usize
+/// where
+///     B: ?Sized + DynDynBaseObject + 'static,
+///     P: DynDyn<'a, B>,
+/// {
+///     ptrs.into_iter()
+///         .filter_map(|p| dyn_dyn::cast::<B, dyn Debug, _>(p).ok())
+///         .count()
+/// }
+///
+/// #[dyn_dyn_base]
+/// trait Base {}
+///
+/// #[derive(Debug)]
+/// struct StructA;
+/// struct StructB;
+///
+/// #[dyn_dyn_impl(Debug)]
+/// impl Base for StructA {}
+///
+/// #[dyn_dyn_impl]
+/// impl Base for StructB {}
+///
+/// let objs: [&dyn Base; 3] = [&StructA, &StructB, &StructA];
+///
+/// assert_eq!(2, count_debug::<dyn Base, _>(objs));
+/// ```
+///
+/// Wrappers around a base trait object can't be cast this way, since the result would point to the wrapper rather than the trait object
+/// that it wraps. Use the outer struct syntax of [`dyn_dyn_cast!`] instead.
+///
+/// ```rust,compile_fail,E0277
+/// # use dyn_dyn::{dyn_dyn_base, dyn_dyn_impl, DynDynBase};
+/// #[dyn_dyn_base]
+/// trait Payload {}
+/// trait Text {}
+///
+/// #[derive(DynDynBase)]
+/// struct Envelope<P: ?Sized> {
+///     id: u64,
+///     payload: P,
+/// }
+///
+/// struct Message;
+///
+/// #[dyn_dyn_impl(Text)]
+/// impl Payload for Message {}
+/// impl Text for Message {}
+///
+/// let envelope: &Envelope<dyn Payload> = &Envelope { id: 1, payload: Message };
+/// let _ = dyn_dyn::cast::<Envelope<dyn Payload>, dyn Text, _>(envelope);
+/// ```
+pub fn cast<'a, B, D, P>(ptr: P) -> Result<<P as DowncastUnchecked<'a>>::DowncastResult<D>, P>
+where
+    B: ?Sized + DynDynBaseObject + 'static,
+    D: ?Sized + Pointee<Metadata = DynMetadata<D>> + 'static,
+    P: DynDyn<'a, B>,
+{
+    if let Some(metadata) = ptr.get_dyn_dyn_table().find::<D>() {
+        // SAFETY: By the safety invariants of GetDynDynTable<B>, the table matches the concrete type of the pointee, so attaching metadata
+        //         retrieved from it to the pointer is valid. Since B is a base trait object rather than a wrapper around one, the pointee is
+        //         the object that the table describes. Since B is 'static, the pointee must also be 'static and so casting it to a 'static
+        //         trait object does not extend any lifetimes.
+        Ok(unsafe { ptr.downcast_unchecked::<D>(metadata) })
+    } else {
+        Err(ptr)
+    }
+}
+
+/// Performs a dynamic downcast of a raw pointer to an object implementing the base trait `B` to a raw pointer to the trait object `D`.
+///
+/// This is equivalent to [`cast`], but accepts `*const T`, `*mut T` and `NonNull<T>` rather than safe pointer types. On success, a pointer
+/// of the same kind with the same data pointer and the metadata for `D` attached is returned. Otherwise, the original pointer is returned
+/// unchanged. As with [`cast`], both `B` and `D` must be `'static` and `B` must be a base trait object rather than a wrapper around one.
+///
+/// # Safety
+///
+/// The provided pointer must point to a live object that is valid for reads for the duration of this call. The returned pointer is subject
+/// to the same aliasing and liveness requirements as the original pointer.
+///
+/// # Examples
+///
+/// ```rust
+/// # use core::fmt::Debug;
+/// # use core::ptr::NonNull;
+/// # use dyn_dyn::{dyn_dyn_base, dyn_dyn_impl};
+/// #[dyn_dyn_base]
+/// trait Base {}
+///
+/// #[derive(Debug)]
+/// struct Struct;
+///
+/// #[dyn_dyn_impl(Debug)]
+/// impl Base for Struct {}
+///
+/// let ptr: NonNull<dyn Base> = NonNull::from(&Struct);
+///
+/// // SAFETY: The pointer points to a live object.
+/// let debug: NonNull<dyn Debug> = unsafe { dyn_dyn::cast_raw::<dyn Base, dyn Debug, _>(ptr) }.unwrap();
+/// assert_eq!(ptr.cast::<()>(), debug.cast::<()>());
+/// ```
+pub unsafe fn cast_raw<B, D, P>(ptr: P) -> Result<P::CastResult<D>, P>
+where
+    B: ?Sized + DynDynBaseObject + 'static,
+    D: ?Sized + Pointee<Metadata = DynMetadata<D>> + 'static,
+    P: DynDynRawPtr<B>,
+{
+    // SAFETY: The caller guarantees that the pointer points to a live object.
+    if let Some(metadata) = unsafe { ptr.get_dyn_dyn_table() }.find::<D>() {
+        // Since B is 'static, the pointee must also be 'static, and the table matches the concrete type of the pointee since it was
+        // retrieved through B, which is a base trait object rather than a wrapper around one. Attaching metadata retrieved from it to the
+        // pointer therefore produces a valid pointer to D.
+        Ok(ptr.with_metadata::<D>(metadata))
+    } else {
+        Err(ptr)
+    }
+}
+
 // SAFETY: The referent of a shared reference will never change unexpectedly and the table returned matches that returned by dereferencing
 //         it by definition. The DowncastUnchecked implementation is also a simple cast via converting to/from a pointer and so should be
 //         correct.
@@ -262,10 +836,12 @@ impl<'a, T: ?Sized> DowncastUnchecked<'a> for &'a T {
     }
 }
 
-// SAFETY: Since T is StableDeref, the results of its Deref implementation should meet the stability requirements and the table returned is
-//         simply passed through from T's GetDynDynTable<B> implementation, which is unsafe itself and can be assumed to be correct. The
-//         DowncastUnchecked implementation defers to the impl for &T::Target, so it should be correct.
-unsafe impl<'a, B: ?Sized + DynDynBase, T: GetDynDynTable<B> + StableDeref + 'a> GetDynDynTable<B>
+// SAFETY: The safety invariants of GetDynDynTable<B> require that T's Deref implementation keeps returning the same reference unless T
+//         itself is mutated or moved. T is borrowed for 'a, so it can't be moved, and this wrapper never mutates it, so T doesn't need to be
+//         StableDeref. The table returned is simply passed through
+//         from T's GetDynDynTable<B> implementation, which is unsafe itself and can be assumed to be correct. The DowncastUnchecked
+//         implementation defers to the impl for &T::Target, so it should be correct.
+unsafe impl<'a, B: ?Sized + DynDynBase, T: GetDynDynTable<B> + Deref + 'a> GetDynDynTable<B>
     for DynDynRef<'a, B, T>
 where
     T::Target: Unsize<B>,
@@ -277,7 +853,7 @@ where
     }
 }
 
-impl<'a, B: ?Sized + DynDynBase, T: DynDyn<'a, B> + StableDeref + 'a> DowncastUnchecked<'a>
+impl<'a, B: ?Sized + DynDynBase, T: GetDynDynTable<B> + Deref + 'a> DowncastUnchecked<'a>
     for DynDynRef<'a, B, T>
 where
     T::Target: Unsize<B>,
@@ -293,6 +869,27 @@ where
     }
 }
 
+impl<'a, B: ?Sized + DynDynBase, T: GetDynDynTable<B> + Deref> Deref for DynDynRef<'a, B, T> {
+    type Target = T::Target;
+
+    fn deref(&self) -> &Self::Target {
+        self.0
+    }
+}
+
+// SAFETY: The safety invariants of GetDynDynTable<B> require that T's Deref implementation keeps returning the same reference unless T
+//         itself is mutated or moved, neither of which can happen while it is borrowed by this wrapper or any of its copies.
+unsafe impl<'a, B: ?Sized + DynDynBase, T: GetDynDynTable<B> + Deref> StableDeref
+    for DynDynRef<'a, B, T>
+{
+}
+
+// SAFETY: See above. Cloning this wrapper just copies the reference to T, so the clone dereferences to the same object.
+unsafe impl<'a, B: ?Sized + DynDynBase, T: GetDynDynTable<B> + Deref> CloneStableDeref
+    for DynDynRef<'a, B, T>
+{
+}
+
 // SAFETY: The referent of a mutable reference will never change unexpectedly and the table is returned by deferring to &T's implementation
 //         and so should be correct. The DowncastUnchecked implementation is also a simple cast via converting to/from a pointer and so
 //         should also be correct.
@@ -318,11 +915,13 @@ impl<'a, T: ?Sized> DowncastUnchecked<'a> for &'a mut T {
     }
 }
 
-// SAFETY: Since T is StableDeref, the results of its Deref and DerefMut implementations should meet the stability requirements and the
-//         table returned is simply passed through from T's GetDynDynTable<B> implementation, which is unsafe itself and can be assumed to
-//         be correct. The DowncastUnchecked implementation defers to the impl for &mut T::Target, so it should be correct.
-unsafe impl<'a, B: ?Sized + DynDynBase, T: DynDyn<'a, B> + StableDeref + DerefMut + 'a>
-    GetDynDynTable<B> for DynDynRefMut<'a, B, T>
+// SAFETY: The safety invariants of GetDynDynTable<B> require that T's Deref and DerefMut implementations keep returning the same reference
+//         unless T itself is mutated or moved and that calling DerefMut::deref_mut does not mutate it. T is borrowed for 'a, so it can't be
+//         moved, and this wrapper never mutates it otherwise, so T doesn't need to be StableDeref. The table returned is simply passed
+//         through from T's GetDynDynTable<B> implementation, which is unsafe itself and can be assumed to be correct. The DowncastUnchecked
+//         implementation defers to the impl for &mut T::Target, so it should be correct.
+unsafe impl<'a, B: ?Sized + DynDynBase, T: GetDynDynTable<B> + DerefMut + 'a> GetDynDynTable<B>
+    for DynDynRefMut<'a, B, T>
 where
     T::Target: Unsize<B>,
 {
@@ -333,8 +932,8 @@ where
     }
 }
 
-impl<'a, B: ?Sized + DynDynBase, T: DynDyn<'a, B> + StableDeref + DerefMut + 'a>
-    DowncastUnchecked<'a> for DynDynRefMut<'a, B, T>
+impl<'a, B: ?Sized + DynDynBase, T: GetDynDynTable<B> + DerefMut + 'a> DowncastUnchecked<'a>
+    for DynDynRefMut<'a, B, T>
 where
     T::Target: Unsize<B>,
 {
@@ -351,11 +950,62 @@ where
     }
 }
 
+impl<'a, B: ?Sized + DynDynBase, T: GetDynDynTable<B> + DerefMut> Deref for DynDynRefMut<'a, B, T> {
+    type Target = T::Target;
+
+    fn deref(&self) -> &Self::Target {
+        self.0
+    }
+}
+
+// SAFETY: Pin<P> dereferences to the same object as P does and can only be mutated through P, so the stability requirements on P's Deref and
+//         DerefMut implementations also hold for it. The table returned is simply passed through from P's GetDynDynTable<B> implementation,
+//         which is unsafe itself and can be assumed to be correct.
+unsafe impl<B: ?Sized + DynDynBase, P: GetDynDynTable<B> + Deref> GetDynDynTable<B> for Pin<P>
+where
+    P::Target: Unsize<B>,
+{
+    type DynTarget = P::DynTarget;
+
+    fn get_dyn_dyn_table(&self) -> DynDynTable {
+        // SAFETY: Pin<P> is repr(transparent) over P, and the shared reference to P is only used to retrieve the table, which can't move the
+        //         object out of it.
+        P::get_dyn_dyn_table(unsafe { &*(self as *const Pin<P> as *const P) })
+    }
+}
+
+impl<'a, T: ?Sized> DowncastUnchecked<'a> for Pin<&'a T> {
+    type DowncastResult<D: ?Sized + 'a> = Pin<&'a D>;
+
+    unsafe fn downcast_unchecked<D: ?Sized + Pointee>(
+        self,
+        metadata: <D as Pointee>::Metadata,
+    ) -> Self::DowncastResult<D> {
+        // SAFETY: The downcast reference points to the same object as the original pinned reference, so it remains pinned. The validity
+        //         of the metadata is guaranteed by this fn's safety invariants.
+        unsafe { Pin::new_unchecked(Pin::into_inner_unchecked(self).downcast_unchecked(metadata)) }
+    }
+}
+
+impl<'a, T: ?Sized> DowncastUnchecked<'a> for Pin<&'a mut T> {
+    type DowncastResult<D: ?Sized + 'a> = Pin<&'a mut D>;
+
+    unsafe fn downcast_unchecked<D: ?Sized + Pointee>(
+        self,
+        metadata: <D as Pointee>::Metadata,
+    ) -> Self::DowncastResult<D> {
+        // SAFETY: The object is never moved out of the reference, and the downcast reference points to the same object as the original
+        //         pinned reference, so it remains pinned. The validity of the metadata is guaranteed by this fn's safety invariants.
+        unsafe { Pin::new_unchecked(Pin::into_inner_unchecked(self).downcast_unchecked(metadata)) }
+    }
+}
+
+// When the allocator-api feature is enabled, these are replaced by the implementations in alloc_api.rs, which support custom allocators.
 cfg_if! {
-    if #[cfg(feature = "alloc")] {
+    if #[cfg(all(feature = "alloc", not(feature = "allocator-api")))] {
         use alloc::boxed::Box;
-        use alloc::sync::Arc;
-        use alloc::rc::Rc;
+        use alloc::sync::{self, Arc};
+        use alloc::rc::{self, Rc};
 
         // SAFETY: Box<T> meets all Deref/DerefMut stability requirements and the table is retrieved by dereferencing it, which is correct
         //         by definition. The DowncastUnchecked implementation is also a simple cast via converting to/from a pointer and so should
@@ -442,5 +1092,96 @@ cfg_if! {
                 }
             }
         }
+
+        // SAFETY: rc::Weak<T> does not implement Deref, so the only requirement is that metadata retrieved from the returned table is valid
+        //         for its pointee. The table is either retrieved from the live pointee through an upgraded pointer, which is correct by
+        //         definition, or is empty if the pointee has already been dropped. The DowncastUnchecked implementation is also a simple
+        //         cast via converting to/from a pointer and so should be correct.
+        unsafe impl<B: ?Sized + DynDynBase, T: ?Sized + Unsize<B>> GetDynDynTable<B> for rc::Weak<T> {
+            type DynTarget = T;
+
+            fn get_dyn_dyn_table(&self) -> DynDynTable {
+                match self.upgrade() {
+                    Some(rc) => B::get_dyn_dyn_table(&*rc),
+                    None => DynDynTable::DROPPED,
+                }
+            }
+        }
+
+        impl<'a, T: ?Sized + 'a> DowncastUnchecked<'a> for rc::Weak<T> {
+            type DowncastResult<D: ?Sized + 'a> = rc::Weak<D>;
+
+            unsafe fn downcast_unchecked<D: ?Sized + Pointee>(self, metadata: <D as Pointee>::Metadata) -> rc::Weak<D> {
+                // SAFETY: rc::Weak::from_raw is fine since the fat pointer passed in has the same data pointer as what we got from
+                //         rc::Weak::into_raw and the metadata pointer is guaranteed to be valid by this fn's safety invariants.
+                unsafe {
+                    rc::Weak::from_raw(
+                        ptr::from_raw_parts(rc::Weak::into_raw(self) as *const (), metadata),
+                    )
+                }
+            }
+        }
+
+        // SAFETY: See the SAFETY comment on the implementation for rc::Weak<T>, which also applies to sync::Weak<T>.
+        unsafe impl<B: ?Sized + DynDynBase, T: ?Sized + Unsize<B>> GetDynDynTable<B> for sync::Weak<T> {
+            type DynTarget = T;
+
+            fn get_dyn_dyn_table(&self) -> DynDynTable {
+                match self.upgrade() {
+                    Some(arc) => B::get_dyn_dyn_table(&*arc),
+                    None => DynDynTable::DROPPED,
+                }
+            }
+        }
+
+        impl<'a, T: ?Sized + 'a> DowncastUnchecked<'a> for sync::Weak<T> {
+            type DowncastResult<D: ?Sized + 'a> = sync::Weak<D>;
+
+            unsafe fn downcast_unchecked<D: ?Sized + Pointee>(self, metadata: <D as Pointee>::Metadata) -> sync::Weak<D> {
+                // SAFETY: sync::Weak::from_raw is fine since the fat pointer passed in has the same data pointer as what we got from
+                //         sync::Weak::into_raw and the metadata pointer is guaranteed to be valid by this fn's safety invariants.
+                unsafe {
+                    sync::Weak::from_raw(
+                        ptr::from_raw_parts(sync::Weak::into_raw(self) as *const (), metadata),
+                    )
+                }
+            }
+        }
+
+        impl<'a, T: ?Sized + 'a> DowncastUnchecked<'a> for Pin<Box<T>> {
+            type DowncastResult<D: ?Sized + 'a> = Pin<Box<D>>;
+
+            unsafe fn downcast_unchecked<D: ?Sized + Pointee>(self, metadata: <D as Pointee>::Metadata) -> Pin<Box<D>> {
+                // SAFETY: The object is never moved out of the Box, and the downcast Box owns the same allocation as the original pinned
+                //         Box, so it remains pinned. The validity of the metadata is guaranteed by this fn's safety invariants.
+                unsafe {
+                    Pin::new_unchecked(Pin::into_inner_unchecked(self).downcast_unchecked(metadata))
+                }
+            }
+        }
+
+        impl<'a, T: ?Sized + 'a> DowncastUnchecked<'a> for Pin<Rc<T>> {
+            type DowncastResult<D: ?Sized + 'a> = Pin<Rc<D>>;
+
+            unsafe fn downcast_unchecked<D: ?Sized + Pointee>(self, metadata: <D as Pointee>::Metadata) -> Pin<Rc<D>> {
+                // SAFETY: The downcast Rc shares the same allocation as the original pinned Rc, so the object remains pinned. The
+                //         validity of the metadata is guaranteed by this fn's safety invariants.
+                unsafe {
+                    Pin::new_unchecked(Pin::into_inner_unchecked(self).downcast_unchecked(metadata))
+                }
+            }
+        }
+
+        impl<'a, T: ?Sized + 'a> DowncastUnchecked<'a> for Pin<Arc<T>> {
+            type DowncastResult<D: ?Sized + 'a> = Pin<Arc<D>>;
+
+            unsafe fn downcast_unchecked<D: ?Sized + Pointee>(self, metadata: <D as Pointee>::Metadata) -> Pin<Arc<D>> {
+                // SAFETY: The downcast Arc shares the same allocation as the original pinned Arc, so the object remains pinned. The
+                //         validity of the metadata is guaranteed by this fn's safety invariants.
+                unsafe {
+                    Pin::new_unchecked(Pin::into_inner_unchecked(self).downcast_unchecked(metadata))
+                }
+            }
+        }
     }
 }
@@ -0,0 +1,29 @@
+use stable_deref_trait::StableDeref;
+
+#[cfg(doc)]
+use crate::{DowncastUnchecked, DynDyn, GetDynDynTable};
+
+/// A custom smart pointer that can be converted to and from a raw pointer to its pointee.
+///
+/// Implementing this trait and using [`#[derive(DynDyn)]`](derive@DynDyn) on the pointer type generates implementations of
+/// [`GetDynDynTable`] and [`DowncastUnchecked`] for it, allowing it to be cast using [`dyn_dyn_cast!`](crate::dyn_dyn_cast). The table is
+/// retrieved by dereferencing the pointer, so it must implement `StableDeref` from the `stable_deref_trait` crate, which is the only unsafe
+/// guarantee that it needs to make.
+///
+/// When casting a pointer with `move`, the pointer returned by [`DynDynPointer::into_raw`] must point to the same object as the pointer
+/// dereferenced to. Since this trait is safe to implement, this is checked when casting, and the cast panics if it doesn't hold.
+pub trait DynDynPointer: Sized + StableDeref {
+    /// The type of this pointer when pointing to an object of type `D` instead, e.g. `Obj<D>` for `Obj<T>`.
+    type Rebind<D: ?Sized>;
+
+    /// Consumes this pointer, returning a raw pointer to the object that it points to.
+    fn into_raw(this: Self) -> *const Self::Target;
+
+    /// Reconstructs a pointer from a raw pointer returned by [`DynDynPointer::into_raw`] with its metadata replaced.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by [`DynDynPointer::into_raw`] on a pointer of type `Self`, with its metadata replaced by valid
+    /// metadata for `D` for the object that it points to.
+    unsafe fn from_raw<D: ?Sized>(ptr: *const D) -> Self::Rebind<D>;
+}
@@ -0,0 +1,76 @@
+use crate::{DynDynBaseObject, DynDynTable};
+use core::marker::Unsize;
+use core::ptr::{self, NonNull, Pointee};
+
+/// A raw pointer to an object implementing the base trait `B` that can be cast using [`cast_raw`](crate::cast_raw).
+///
+/// This trait is implemented for `*const T`, `*mut T` and `NonNull<T>` and cannot be implemented outside of this crate. `B` must be a base
+/// trait object rather than a wrapper around one, since the metadata found in the table is attached directly to the pointer.
+pub trait DynDynRawPtr<B: ?Sized + DynDynBaseObject>: Copy + private::Sealed {
+    /// The type of raw pointer produced by attaching the metadata for `D` to this pointer.
+    type CastResult<D: ?Sized>;
+
+    /// Gets the [`DynDynTable`] of the object that this pointer points to.
+    ///
+    /// # Safety
+    ///
+    /// This pointer must point to a live object that is valid for reads.
+    unsafe fn get_dyn_dyn_table(self) -> DynDynTable;
+
+    /// Replaces the metadata of this pointer with the provided metadata, keeping the same data pointer.
+    fn with_metadata<D: ?Sized + Pointee>(
+        self,
+        metadata: <D as Pointee>::Metadata,
+    ) -> Self::CastResult<D>;
+}
+
+impl<B: ?Sized + DynDynBaseObject, T: ?Sized + Unsize<B>> DynDynRawPtr<B> for *const T {
+    type CastResult<D: ?Sized> = *const D;
+
+    unsafe fn get_dyn_dyn_table(self) -> DynDynTable {
+        let ptr: *const B = self;
+
+        // SAFETY: The caller guarantees that this pointer points to a live object.
+        B::get_dyn_dyn_table(unsafe { &*ptr })
+    }
+
+    fn with_metadata<D: ?Sized + Pointee>(self, metadata: <D as Pointee>::Metadata) -> *const D {
+        ptr::from_raw_parts(self as *const (), metadata)
+    }
+}
+
+impl<B: ?Sized + DynDynBaseObject, T: ?Sized + Unsize<B>> DynDynRawPtr<B> for *mut T {
+    type CastResult<D: ?Sized> = *mut D;
+
+    unsafe fn get_dyn_dyn_table(self) -> DynDynTable {
+        // SAFETY: Invariants are passed through.
+        unsafe { <*const T as DynDynRawPtr<B>>::get_dyn_dyn_table(self) }
+    }
+
+    fn with_metadata<D: ?Sized + Pointee>(self, metadata: <D as Pointee>::Metadata) -> *mut D {
+        ptr::from_raw_parts_mut(self as *mut (), metadata)
+    }
+}
+
+impl<B: ?Sized + DynDynBaseObject, T: ?Sized + Unsize<B>> DynDynRawPtr<B> for NonNull<T> {
+    type CastResult<D: ?Sized> = NonNull<D>;
+
+    unsafe fn get_dyn_dyn_table(self) -> DynDynTable {
+        // SAFETY: Invariants are passed through.
+        unsafe { <*const T as DynDynRawPtr<B>>::get_dyn_dyn_table(self.as_ptr()) }
+    }
+
+    fn with_metadata<D: ?Sized + Pointee>(self, metadata: <D as Pointee>::Metadata) -> NonNull<D> {
+        NonNull::from_raw_parts(self.cast::<()>(), metadata)
+    }
+}
+
+mod private {
+    use core::ptr::NonNull;
+
+    pub trait Sealed {}
+
+    impl<T: ?Sized> Sealed for *const T {}
+    impl<T: ?Sized> Sealed for *mut T {}
+    impl<T: ?Sized> Sealed for NonNull<T> {}
+}
@@ -1,13 +1,15 @@
 use crate::cast_target::DynDynCastTarget;
+use crate::internal::LifetimeKey;
 use cfg_if::cfg_if;
-use core::any::TypeId;
+use core::alloc::Layout;
+use core::any::{Any, TypeId};
 use core::fmt::{self, Debug};
 use core::marker::Unsize;
 use core::mem;
 use core::ptr::{self, DynMetadata, Pointee};
 
 #[cfg(doc)]
-use crate::dyn_dyn_cast;
+use crate::{dyn_dyn_cast, dyn_dyn_downcast};
 
 /// An untyped metadata that corresponds to the metadata that would be used for a trait object.
 #[derive(Debug, Clone, Copy)]
@@ -33,6 +35,12 @@ impl AnyDynMetadata {
         //         should be safe.
         unsafe { mem::transmute::<*const (), DynMetadata<T>>(self.0) }
     }
+
+    fn erased(self) -> DynMetadata<dyn Any> {
+        // SAFETY: The layout of DynMetadata<T> does not depend on T and the size, alignment and drop entries at the start of the vtable
+        //         that it points to are common to all vtables, so it's fine to look at them through any DynMetadata type.
+        unsafe { mem::transmute::<*const (), DynMetadata<dyn Any>>(self.0) }
+    }
 }
 
 impl<T: ?Sized> From<DynMetadata<T>> for AnyDynMetadata {
@@ -91,11 +99,12 @@ impl Eq for DynInfo {}
 
 /// An entry in a concrete type's table of downcast-exposed traits.
 ///
-/// Each entry represents a single trait object that the concrete type in question can be downcast to. Note that entries will only appear
-/// for bare trait object types, i.e. `dyn Trait`. Trait objects with extra marker types, e.g. `dyn Trait + Send`, are handled specially
-/// by the [`dyn_dyn_cast!`] macro and do not appear in a concrete type's trait table.
+/// Each entry represents a single trait object that the concrete type in question can be downcast to. Trait objects with extra auto traits,
+/// e.g. `dyn Trait + Send`, are distinct types from the bare trait object and will only appear in a concrete type's trait table if they were
+/// explicitly exposed.
 pub struct DynDynTableEntry {
     ty: DynInfo,
+    name: &'static str,
     meta: AnyDynMetadata,
 }
 
@@ -113,14 +122,36 @@ impl DynDynTableEntry {
         T: Unsize<D>,
         D: ?Sized + Pointee<Metadata = DynMetadata<M>> + 'static,
         M: ?Sized,
-    >() -> DynDynTableEntry {
+    >(
+        name: &'static str,
+    ) -> DynDynTableEntry {
         DynDynTableEntry {
             ty: DynInfo::of::<D>(),
+            name,
+            meta: AnyDynMetadata::upcast(Self::meta_for_ty::<T, D, M>()),
+        }
+    }
+
+    #[doc(hidden)] // public only because it's used by #[dyn_dyn_impl]
+    pub const fn new_with_lifetimes<
+        T: Unsize<D>,
+        D: ?Sized + Pointee<Metadata = DynMetadata<M>> + 'static,
+        L: 'static,
+        M: ?Sized,
+    >(
+        name: &'static str,
+    ) -> DynDynTableEntry {
+        DynDynTableEntry {
+            ty: DynInfo(TypeId::of::<LifetimeKey<D, L>>(), type_name::<D>()),
+            name,
             meta: AnyDynMetadata::upcast(Self::meta_for_ty::<T, D, M>()),
         }
     }
 
     /// Gets the [`TypeId`] of the trait object corresponding to this entry.
+    ///
+    /// If the trait object has lifetime arguments other than `'static`, then this is instead an opaque identifier that also records which
+    /// lifetime arguments of the base trait those lifetimes correspond to.
     pub fn type_id(&self) -> TypeId {
         self.ty.type_id()
     }
@@ -130,6 +161,30 @@ impl DynDynTableEntry {
     pub fn type_name(&self) -> &'static str {
         self.ty.name()
     }
+
+    /// Gets the name of the trait object corresponding to this entry as it was written in [`#[dyn_dyn_impl]`](crate::dyn_dyn_impl), e.g.
+    /// `dyn Trait<T>`.
+    ///
+    /// Unlike `DynDynTableEntry::type_name`, this is available without the `dynamic-names` feature. However, paths are not resolved and
+    /// generic parameters are not substituted, so the name is only meant to be displayed to humans.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Gets the metadata for the trait object corresponding to this entry.
+    pub fn metadata(&self) -> AnyDynMetadata {
+        self.meta
+    }
+
+    /// Gets the size of the concrete type, as recorded in the vtable of the trait object corresponding to this entry.
+    pub fn size_of(&self) -> usize {
+        self.meta.erased().size_of()
+    }
+
+    /// Gets the alignment of the concrete type, as recorded in the vtable of the trait object corresponding to this entry.
+    pub fn align_of(&self) -> usize {
+        self.meta.erased().align_of()
+    }
 }
 
 impl Debug for DynDynTableEntry {
@@ -145,14 +200,45 @@ impl Debug for DynDynTableEntry {
 
     #[cfg(not(feature = "dynamic-names"))]
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "DynDynTableEntry({:?}: {:?})", self.type_id(), self.meta)
+        write!(f, "DynDynTableEntry(<{}>: {:?})", self.name, self.meta)
     }
 }
 
+#[derive(Debug)]
+struct ConcreteInfo {
+    ty: DynInfo,
+    layout: Layout,
+    lifetime_erased: bool,
+    unknown: bool,
+}
+
+impl ConcreteInfo {
+    const fn of<T: 'static>(lifetime_erased: bool) -> ConcreteInfo {
+        ConcreteInfo {
+            ty: DynInfo::of::<T>(),
+            layout: Layout::new::<T>(),
+            lifetime_erased,
+            unknown: false,
+        }
+    }
+
+    const UNKNOWN: ConcreteInfo = ConcreteInfo {
+        unknown: true,
+        ..ConcreteInfo::of::<UnknownObject>(false)
+    };
+}
+
+// Used as the concrete type of tables that don't know the concrete type of the object they were retrieved from, e.g. the table returned
+// for a weak pointer whose referent has already been dropped, since the table of the referent can no longer be retrieved at that point.
+struct UnknownObject;
+
 /// A table of trait object types that a concrete type can be downcast to.
+///
+/// The entries in this table appear in the order that the corresponding traits were listed in [`#[dyn_dyn_impl]`](crate::dyn_dyn_impl).
 #[derive(Debug, Clone, Copy)]
 pub struct DynDynTable {
     traits: &'static [DynDynTableEntry],
+    concrete: &'static ConcreteInfo,
 }
 
 impl DynDynTable {
@@ -172,14 +258,119 @@ impl DynDynTable {
         })
     }
 
+    /// Gets the [`TypeId`] of the concrete type of the object that this table was retrieved from.
+    ///
+    /// If the concrete type has lifetime parameters, then this is the [`TypeId`] of that type with all of its lifetime parameters replaced
+    /// by `'static`. If the table doesn't know the concrete type, this is the [`TypeId`] of a private placeholder type that can't be named
+    /// outside of this crate. This is the case for the table of a weak pointer whose referent has already been dropped, of a type whose
+    /// [`#[dyn_dyn_impl]`](crate::dyn_dyn_impl) block has type parameters that aren't bound by `'static` and of a wrapper around a base
+    /// trait object, such as `Cell<dyn Base>` or a struct using [`#[derive(DynDynBase)]`](derive@crate::DynDynBase).
+    pub fn type_id(&self) -> TypeId {
+        self.concrete.ty.type_id()
+    }
+
+    /// Gets a human-readable name representing the concrete type of the object that this table was retrieved from.
+    #[cfg(feature = "dynamic-names")]
+    pub fn type_name(&self) -> &'static str {
+        self.concrete.ty.name()
+    }
+
+    /// Gets the [`Layout`] of the concrete type of the object that this table was retrieved from.
+    ///
+    /// If the table doesn't know the concrete type, as described in [`DynDynTable::type_id`], this is the layout of a zero-sized type with
+    /// an alignment of 1.
+    pub fn layout(&self) -> Layout {
+        self.concrete.layout
+    }
+
+    /// Returns `true` if both tables were retrieved from objects having the same concrete type.
+    ///
+    /// This always returns `false` if either table doesn't know the concrete type, as described in [`DynDynTable::type_id`].
+    pub fn same_concrete_type(a: &DynDynTable, b: &DynDynTable) -> bool {
+        !a.concrete.unknown && !b.concrete.unknown && a.concrete.ty == b.concrete.ty
+    }
+
+    /// Returns `true` if the concrete type of the object that this table was retrieved from is `T`.
+    ///
+    /// This is used by the [`dyn_dyn_downcast!`] macro to determine whether a pointer can be downcast to a pointer to its concrete type.
+    /// Since it's not possible to recover the lifetimes of a concrete type with lifetime parameters, this always returns `false` for such
+    /// types. It also always returns `false` if the table doesn't know the concrete type, as described in [`DynDynTable::type_id`].
+    pub fn is<T: 'static>(&self) -> bool {
+        !self.concrete.lifetime_erased
+            && !self.concrete.unknown
+            && self.type_id() == TypeId::of::<T>()
+    }
+
+    /// Returns `true` if this table contains an entry for the trait object `D`, i.e. if the object that this table was retrieved from can be
+    /// cast to `D`.
+    pub fn can_cast<D: ?Sized + DynDynCastTarget + 'static>(&self) -> bool {
+        self.find_untyped(TypeId::of::<D>()).is_some()
+    }
+
+    /// Gets the number of entries in this table.
+    pub fn len(&self) -> usize {
+        self.traits.len()
+    }
+
+    /// Returns `true` if this table has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.traits.is_empty()
+    }
+
+    /// Returns a copy of this table with the same entries, but which doesn't record the concrete type of the object that it was retrieved
+    /// from.
+    ///
+    /// This is intended for implementations of [`DynDynBase`](crate::DynDynBase) on structs wrapping a base trait object, which pass through
+    /// the table of the object they wrap. That table records the concrete type of the wrapped object rather than that of the wrapper, so
+    /// returning it unchanged would allow the wrapper to be downcast into the wrong type.
+    pub const fn without_concrete_type(self) -> DynDynTable {
+        DynDynTable {
+            traits: self.traits,
+            concrete: &ConcreteInfo::UNKNOWN,
+        }
+    }
+
     /// Returns a reference to the slice of entries in this table
     pub fn into_slice(self) -> &'static [DynDynTableEntry] {
         self.traits
     }
 
-    #[doc(hidden)]
-    pub const fn new(traits: &'static [DynDynTableEntry]) -> DynDynTable {
-        DynDynTable { traits }
+    #[cfg(feature = "alloc")]
+    pub(crate) const DROPPED: DynDynTable = DynDynTable {
+        traits: &[],
+        concrete: &ConcreteInfo::UNKNOWN,
+    };
+
+    // T must be the concrete type of the object that the table is returned for, since DynDynTable::is allows downcasting the object to it.
+    // Concrete types with lifetime parameters must use new_lifetime_erased instead.
+    #[doc(hidden)] // public only because it's used by #[dyn_dyn_impl]
+    pub const fn new<T: 'static>(traits: &'static [DynDynTableEntry]) -> DynDynTable {
+        DynDynTable {
+            traits,
+            concrete: &const { ConcreteInfo::of::<T>(false) },
+        }
+    }
+
+    // T must be the concrete type of the object that the table is returned for with its lifetimes replaced by 'static. The resulting table
+    // can't be downcast into, since the original lifetimes can't be recovered.
+    #[doc(hidden)] // public only because it's used by #[dyn_dyn_impl]
+    pub const fn new_lifetime_erased<T: 'static>(
+        traits: &'static [DynDynTableEntry],
+    ) -> DynDynTable {
+        DynDynTable {
+            traits,
+            concrete: &const { ConcreteInfo::of::<T>(true) },
+        }
+    }
+
+    // Used when the concrete type of the object that the table is returned for might not be 'static, so its TypeId can't be retrieved. The
+    // resulting table can't be downcast into.
+    #[doc(hidden)] // public only because it's used by #[dyn_dyn_impl]
+    pub const fn new_unknown(traits: &'static [DynDynTableEntry]) -> DynDynTable {
+        DynDynTable {
+            traits,
+            concrete: &ConcreteInfo::UNKNOWN,
+        }
     }
 }
 
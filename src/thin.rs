@@ -0,0 +1,386 @@
+use crate::{DynDynBase, DynDynTable, GetDynDynTable};
+use alloc::alloc::{alloc, dealloc, handle_alloc_error, Layout};
+use cfg_if::cfg_if;
+use core::fmt::{self, Debug, Display, Pointer};
+use core::marker::{PhantomData, Unsize};
+use core::ops::{Deref, DerefMut};
+use core::ptr::{self, DynMetadata, NonNull, Pointee};
+use core::sync::atomic::{self, AtomicUsize, Ordering};
+use stable_deref_trait::{CloneStableDeref, StableDeref};
+
+// The header is placed at the start of the allocation and the value follows it, padded to its own alignment. Since the layout of the
+// header does not depend on the concrete type of the value, the offset of the value can always be recomputed from the metadata alone.
+#[repr(C)]
+struct ThinHeader<B: ?Sized, C> {
+    count: C,
+    metadata: DynMetadata<B>,
+    table: DynDynTable,
+}
+
+// A pointer to an allocation made by ThinPtr::new. All of the handles owning such a pointer guarantee that the allocation is live for as
+// long as the ThinPtr is reachable through them.
+struct ThinPtr<B: ?Sized, C>(NonNull<ThinHeader<B, C>>);
+
+impl<B: ?Sized + DynDynBase + Pointee<Metadata = DynMetadata<B>>, C> ThinPtr<B, C> {
+    fn layout(metadata: DynMetadata<B>) -> (Layout, usize) {
+        Layout::new::<ThinHeader<B, C>>()
+            .extend(metadata.layout())
+            .expect("object is too large to allocate")
+    }
+
+    fn new<T: Unsize<B>>(count: C, value: T) -> Self {
+        let metadata = ptr::metadata(&value as &B as *const B);
+        let table = B::get_dyn_dyn_table(&value);
+        let (layout, offset) = Self::layout(metadata);
+
+        // SAFETY: The layout always has a non-zero size, since it includes the header, which contains at least one pointer.
+        let Some(raw) = NonNull::new(unsafe { alloc(layout) }) else {
+            handle_alloc_error(layout)
+        };
+
+        // SAFETY: The allocation was just made with a layout that places a header at its start and a T at the given offset, so both of
+        //         these writes are in bounds and properly aligned.
+        unsafe {
+            raw.cast::<ThinHeader<B, C>>().write(ThinHeader {
+                count,
+                metadata,
+                table,
+            });
+            raw.add(offset).cast::<T>().write(value);
+        }
+
+        ThinPtr(raw.cast())
+    }
+
+    fn header(&self) -> &ThinHeader<B, C> {
+        // SAFETY: The allocation is kept live by the handle through which this ThinPtr is reachable and the header is never mutated other
+        //         than through the count, which must use interior mutability if it can change.
+        unsafe { self.0.as_ref() }
+    }
+
+    fn value(&self) -> *mut B {
+        let metadata = self.header().metadata;
+        let (_, offset) = Self::layout(metadata);
+
+        // SAFETY: This is the same offset at which the value was written when the allocation was made, so it is in bounds.
+        ptr::from_raw_parts_mut(
+            unsafe { self.0.cast::<u8>().add(offset) }.as_ptr(),
+            metadata,
+        )
+    }
+
+    /// # Safety
+    ///
+    /// There must be no other handles to this allocation and no outstanding references to the value.
+    unsafe fn drop_and_dealloc(&mut self) {
+        let (layout, _) = Self::layout(self.header().metadata);
+
+        // SAFETY: The caller guarantees that nothing else can observe the value after this point, so dropping it in place and freeing the
+        //         allocation with the same layout it was allocated with is fine. The header itself never needs to be dropped, since the
+        //         count types used with it have no drop glue.
+        unsafe {
+            ptr::drop_in_place(self.value());
+            dealloc(self.0.as_ptr().cast(), layout);
+        }
+    }
+}
+
+/// An owning pointer to an object that can be downcast via the base trait object `B`, which is only a single pointer wide.
+///
+/// Rather than storing the trait object metadata alongside the pointer, the metadata and the [`DynDynTable`] of the object are stored in a
+/// header at the start of its heap allocation. The table is retrieved once when the object is boxed and cached there, so casts performed
+/// on a thin box do not call [`DynDynBase::get_dyn_dyn_table`]. Like [`DynDynFat`](crate::DynDynFat), this trades a small amount of memory
+/// for avoiding the repeated indirect calls, but keeps the pointer itself thin.
+///
+/// Thin boxes can be cast by reference and by mutable reference. Casting by value is not supported, since the allocation can't be handed
+/// off to a [`Box`](alloc::boxed::Box).
+///
+/// # Examples
+///
+/// ```rust
+/// # use core::fmt::Debug;
+/// # use dyn_dyn::{dyn_dyn_base, dyn_dyn_cast, dyn_dyn_impl, DynDynThinBox};
+/// #[dyn_dyn_base]
+/// trait Base {}
+///
+/// #[derive(Debug)]
+/// struct Struct(u32);
+///
+/// #[dyn_dyn_impl(Debug)]
+/// impl Base for Struct {}
+///
+/// let thin: DynDynThinBox<dyn Base> = DynDynThinBox::new(Struct(42));
+/// assert_eq!(core::mem::size_of_val(&thin), core::mem::size_of::<usize>());
+///
+/// let debug: &dyn Debug = dyn_dyn_cast!(Base => Debug, &thin).ok().unwrap();
+/// assert_eq!("Struct(42)", format!("{:?}", debug));
+/// ```
+pub struct DynDynThinBox<B: ?Sized + DynDynBase + Pointee<Metadata = DynMetadata<B>>> {
+    ptr: ThinPtr<B, ()>,
+    _value: PhantomData<B>,
+}
+
+impl<B: ?Sized + DynDynBase + Pointee<Metadata = DynMetadata<B>>> DynDynThinBox<B> {
+    /// Moves the provided value into a new heap allocation, caching its [`DynDynTable`] in the allocation's header.
+    pub fn new<T: Unsize<B>>(value: T) -> Self {
+        DynDynThinBox {
+            ptr: ThinPtr::new((), value),
+            _value: PhantomData,
+        }
+    }
+
+    /// Gets the [`DynDynTable`] of the object referenced by a thin box without dereferencing the object itself.
+    pub fn get_dyn_dyn_table(ptr: &Self) -> DynDynTable {
+        ptr.ptr.header().table
+    }
+}
+
+impl<B: ?Sized + DynDynBase + Pointee<Metadata = DynMetadata<B>>> Deref for DynDynThinBox<B> {
+    type Target = B;
+
+    fn deref(&self) -> &B {
+        // SAFETY: The value is live for as long as the box is and can only be mutated through &mut self.
+        unsafe { &*self.ptr.value() }
+    }
+}
+
+impl<B: ?Sized + DynDynBase + Pointee<Metadata = DynMetadata<B>>> DerefMut for DynDynThinBox<B> {
+    fn deref_mut(&mut self) -> &mut B {
+        // SAFETY: The box uniquely owns the value, so borrowing it mutably through &mut self is fine.
+        unsafe { &mut *self.ptr.value() }
+    }
+}
+
+impl<B: ?Sized + DynDynBase + Pointee<Metadata = DynMetadata<B>>> Drop for DynDynThinBox<B> {
+    fn drop(&mut self) {
+        // SAFETY: The box uniquely owns the allocation and is being dropped, so nothing else can observe the value.
+        unsafe { self.ptr.drop_and_dealloc() }
+    }
+}
+
+// SAFETY: The box uniquely owns its value, so it can be sent or shared across threads whenever the value itself could be.
+unsafe impl<B: ?Sized + DynDynBase + Pointee<Metadata = DynMetadata<B>> + Send> Send
+    for DynDynThinBox<B>
+{
+}
+
+// SAFETY: See above.
+unsafe impl<B: ?Sized + DynDynBase + Pointee<Metadata = DynMetadata<B>> + Sync> Sync
+    for DynDynThinBox<B>
+{
+}
+
+// SAFETY: The value lives in a heap allocation that never moves for the lifetime of the box.
+unsafe impl<B: ?Sized + DynDynBase + Pointee<Metadata = DynMetadata<B>>> StableDeref
+    for DynDynThinBox<B>
+{
+}
+
+// SAFETY: The table was retrieved from the value when the box was created and is never changed afterwards, and the table of an object can
+//         never change through its lifetime.
+unsafe impl<B: ?Sized + DynDynBase + Pointee<Metadata = DynMetadata<B>> + Unsize<B>>
+    GetDynDynTable<B> for DynDynThinBox<B>
+{
+    type DynTarget = B;
+
+    fn get_dyn_dyn_table(&self) -> DynDynTable {
+        DynDynThinBox::get_dyn_dyn_table(self)
+    }
+}
+
+impl<B: ?Sized + DynDynBase + Pointee<Metadata = DynMetadata<B>> + Debug> Debug
+    for DynDynThinBox<B>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl<B: ?Sized + DynDynBase + Pointee<Metadata = DynMetadata<B>> + Display> Display
+    for DynDynThinBox<B>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&**self, f)
+    }
+}
+
+impl<B: ?Sized + DynDynBase + Pointee<Metadata = DynMetadata<B>>> Pointer for DynDynThinBox<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Pointer::fmt(&self.ptr.value(), f)
+    }
+}
+
+/// A reference-counted version of [`DynDynThinBox`], which is only a single pointer wide.
+///
+/// The reference count is stored in the same header as the trait object metadata and the cached [`DynDynTable`]. Cloning a thin
+/// [`Arc`](alloc::sync::Arc) produces another pointer to the same allocation, so the table is still only retrieved once when the object is
+/// first allocated. Weak references are not supported.
+///
+/// # Examples
+///
+/// ```rust
+/// # use core::fmt::Debug;
+/// # use dyn_dyn::{dyn_dyn_base, dyn_dyn_cast, dyn_dyn_impl, DynDynThinArc};
+/// #[dyn_dyn_base]
+/// trait Base {}
+///
+/// #[derive(Debug)]
+/// struct Struct(u32);
+///
+/// #[dyn_dyn_impl(Debug)]
+/// impl Base for Struct {}
+///
+/// let thin: DynDynThinArc<dyn Base> = DynDynThinArc::new(Struct(42));
+/// let clone = thin.clone();
+/// assert_eq!(2, DynDynThinArc::strong_count(&thin));
+///
+/// let debug: &dyn Debug = dyn_dyn_cast!(Base => Debug, &clone).ok().unwrap();
+/// assert_eq!("Struct(42)", format!("{:?}", debug));
+/// ```
+pub struct DynDynThinArc<B: ?Sized + DynDynBase + Pointee<Metadata = DynMetadata<B>>> {
+    ptr: ThinPtr<B, AtomicUsize>,
+    _value: PhantomData<B>,
+}
+
+impl<B: ?Sized + DynDynBase + Pointee<Metadata = DynMetadata<B>>> DynDynThinArc<B> {
+    /// Moves the provided value into a new reference-counted heap allocation, caching its [`DynDynTable`] in the allocation's header.
+    pub fn new<T: Unsize<B>>(value: T) -> Self {
+        DynDynThinArc {
+            ptr: ThinPtr::new(AtomicUsize::new(1), value),
+            _value: PhantomData,
+        }
+    }
+
+    /// Gets the [`DynDynTable`] of the object referenced by a thin [`Arc`](alloc::sync::Arc) without dereferencing the object itself.
+    pub fn get_dyn_dyn_table(ptr: &Self) -> DynDynTable {
+        ptr.ptr.header().table
+    }
+
+    /// Gets the number of pointers to the same allocation as the provided pointer.
+    pub fn strong_count(ptr: &Self) -> usize {
+        ptr.ptr.header().count.load(Ordering::Acquire)
+    }
+
+    /// Returns `true` if both pointers point to the same allocation.
+    pub fn ptr_eq(a: &Self, b: &Self) -> bool {
+        a.ptr.0 == b.ptr.0
+    }
+
+    /// Returns a mutable reference to the object if there are no other pointers to the same allocation.
+    pub fn get_mut(ptr: &mut Self) -> Option<&mut B> {
+        if ptr.ptr.header().count.load(Ordering::Acquire) == 1 {
+            // SAFETY: This is the only pointer to the allocation and it is borrowed mutably, so nothing else can access the value.
+            Some(unsafe { &mut *ptr.ptr.value() })
+        } else {
+            None
+        }
+    }
+}
+
+fn abort() -> ! {
+    cfg_if! {
+        if #[cfg(feature = "std")] {
+            std::process::abort()
+        } else {
+            core::intrinsics::abort()
+        }
+    }
+}
+
+impl<B: ?Sized + DynDynBase + Pointee<Metadata = DynMetadata<B>>> Clone for DynDynThinArc<B> {
+    fn clone(&self) -> Self {
+        let old = self.ptr.header().count.fetch_add(1, Ordering::Relaxed);
+
+        // Mirror the standard library's protection against reference count overflows caused by leaking clones. This must abort rather than
+        // panic, since other threads may keep cloning the pointer while this one unwinds and wrap the count around before it is fixed up.
+        if old > isize::MAX as usize {
+            abort();
+        }
+
+        DynDynThinArc {
+            ptr: ThinPtr(self.ptr.0),
+            _value: PhantomData,
+        }
+    }
+}
+
+impl<B: ?Sized + DynDynBase + Pointee<Metadata = DynMetadata<B>>> Deref for DynDynThinArc<B> {
+    type Target = B;
+
+    fn deref(&self) -> &B {
+        // SAFETY: The value is live for as long as any pointer to it is and is never mutated through a shared reference.
+        unsafe { &*self.ptr.value() }
+    }
+}
+
+impl<B: ?Sized + DynDynBase + Pointee<Metadata = DynMetadata<B>>> Drop for DynDynThinArc<B> {
+    fn drop(&mut self) {
+        if self.ptr.header().count.fetch_sub(1, Ordering::Release) != 1 {
+            return;
+        }
+
+        atomic::fence(Ordering::Acquire);
+
+        // SAFETY: This was the last pointer to the allocation and the fence above ensures that all uses of the value through other pointers
+        //         happen before it is dropped.
+        unsafe { self.ptr.drop_and_dealloc() }
+    }
+}
+
+// SAFETY: Like Arc<T>, the value can be accessed and dropped from any thread holding a pointer, so it must be both Send and Sync.
+unsafe impl<B: ?Sized + DynDynBase + Pointee<Metadata = DynMetadata<B>> + Send + Sync> Send
+    for DynDynThinArc<B>
+{
+}
+
+// SAFETY: See above.
+unsafe impl<B: ?Sized + DynDynBase + Pointee<Metadata = DynMetadata<B>> + Send + Sync> Sync
+    for DynDynThinArc<B>
+{
+}
+
+// SAFETY: The value lives in a heap allocation that never moves while any pointer to it exists.
+unsafe impl<B: ?Sized + DynDynBase + Pointee<Metadata = DynMetadata<B>>> StableDeref
+    for DynDynThinArc<B>
+{
+}
+
+// SAFETY: Clones point to the same allocation, so they dereference to the same value.
+unsafe impl<B: ?Sized + DynDynBase + Pointee<Metadata = DynMetadata<B>>> CloneStableDeref
+    for DynDynThinArc<B>
+{
+}
+
+// SAFETY: The table was retrieved from the value when the allocation was created and is never changed afterwards, and the table of an
+//         object can never change through its lifetime.
+unsafe impl<B: ?Sized + DynDynBase + Pointee<Metadata = DynMetadata<B>> + Unsize<B>>
+    GetDynDynTable<B> for DynDynThinArc<B>
+{
+    type DynTarget = B;
+
+    fn get_dyn_dyn_table(&self) -> DynDynTable {
+        DynDynThinArc::get_dyn_dyn_table(self)
+    }
+}
+
+impl<B: ?Sized + DynDynBase + Pointee<Metadata = DynMetadata<B>> + Debug> Debug
+    for DynDynThinArc<B>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl<B: ?Sized + DynDynBase + Pointee<Metadata = DynMetadata<B>> + Display> Display
+    for DynDynThinArc<B>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&**self, f)
+    }
+}
+
+impl<B: ?Sized + DynDynBase + Pointee<Metadata = DynMetadata<B>>> Pointer for DynDynThinArc<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Pointer::fmt(&self.ptr.value(), f)
+    }
+}
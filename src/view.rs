@@ -0,0 +1,238 @@
+use crate::{DynDynBaseObject, DynDynFat, DynDynTable, GetDynDynTable};
+use core::fmt::{self, Debug, Display};
+use core::marker::{PhantomData, Unsize};
+use core::ops::{Deref, DerefMut};
+use core::ptr::{self, DynMetadata, Pointee};
+use stable_deref_trait::{CloneStableDeref, StableDeref};
+
+/// A pointer to an object that can be downcast via the base trait object `B`, viewed as the trait object `D`.
+///
+/// A view holds the original pointer to the object, its cached [`DynDynTable`] and the trait object metadata for `D`. It dereferences to
+/// `D`, but unlike the result of a regular cast, the object can still be accessed as `B` through [`DynDynView::as_base`], cast to another
+/// trait object through [`DynDynView::recast`] without retrieving the table again, or turned back into the original pointer.
+///
+/// As with [`cast`](crate::cast), both `B` and `D` must be `'static` and `B` must be a base trait object rather than a wrapper around one.
+///
+/// # Examples
+///
+/// ```rust
+/// # use dyn_dyn::{dyn_dyn_base, dyn_dyn_impl, DynDynView};
+/// #[dyn_dyn_base]
+/// trait Component {
+///     fn name(&self) -> &'static str;
+/// }
+///
+/// trait Health {
+///     fn health(&self) -> u32;
+/// }
+///
+/// trait Position {
+///     fn position(&self) -> (i32, i32);
+/// }
+///
+/// struct Player;
+///
+/// #[dyn_dyn_impl(Health, Position)]
+/// impl Component for Player {
+///     fn name(&self) -> &'static str {
+///         "player"
+///     }
+/// }
+///
+/// impl Health for Player {
+///     fn health(&self) -> u32 {
+///         100
+///     }
+/// }
+///
+/// impl Position for Player {
+///     fn position(&self) -> (i32, i32) {
+///         (1, 2)
+///     }
+/// }
+///
+/// let player: &dyn Component = &Player;
+/// let health: DynDynView<dyn Component, dyn Health, _> = DynDynView::new(player).ok().unwrap();
+///
+/// assert_eq!(100, health.health());
+/// assert_eq!("player", DynDynView::as_base(&health).name());
+///
+/// let position: DynDynView<dyn Component, dyn Position, _> = DynDynView::recast(health).ok().unwrap();
+/// assert_eq!((1, 2), position.position());
+/// ```
+pub struct DynDynView<B: ?Sized + DynDynBaseObject, D: ?Sized, P> {
+    fat: DynDynFat<B, P>,
+    metadata: DynMetadata<D>,
+    _target: PhantomData<fn(D) -> D>,
+}
+
+impl<B, D, P> DynDynView<B, D, P>
+where
+    B: ?Sized + DynDynBaseObject + 'static,
+    D: ?Sized + Pointee<Metadata = DynMetadata<D>> + 'static,
+    P: GetDynDynTable<B> + Deref,
+    P::Target: Unsize<B>,
+{
+    /// Attempts to create a view of the object referenced by the provided pointer as the trait object `D`. This retrieves the pointer's
+    /// [`DynDynTable`] once and caches it for any further casts. If the object can't be cast to `D`, the pointer is returned unchanged.
+    pub fn new(ptr: P) -> Result<Self, P> {
+        DynDynView::from_fat(DynDynFat::new(ptr)).map_err(DynDynFat::unwrap)
+    }
+
+    /// Attempts to create a view of the object referenced by the provided fat pointer as the trait object `D`, using the fat pointer's
+    /// cached [`DynDynTable`]. If the object can't be cast to `D`, the fat pointer is returned unchanged.
+    pub fn from_fat(ptr: DynDynFat<B, P>) -> Result<Self, DynDynFat<B, P>> {
+        match DynDynFat::get_dyn_dyn_table(&ptr).find::<D>() {
+            Some(metadata) => Ok(DynDynView {
+                fat: ptr,
+                metadata,
+                _target: PhantomData,
+            }),
+            None => Err(ptr),
+        }
+    }
+
+    /// Attempts to view the same object as the trait object `D2` using the cached [`DynDynTable`]. If the object can't be cast to `D2`, the
+    /// original view is returned unchanged.
+    pub fn recast<D2>(ptr: Self) -> Result<DynDynView<B, D2, P>, Self>
+    where
+        D2: ?Sized + Pointee<Metadata = DynMetadata<D2>> + 'static,
+    {
+        match DynDynFat::get_dyn_dyn_table(&ptr.fat).find::<D2>() {
+            Some(metadata) => Ok(DynDynView {
+                fat: ptr.fat,
+                metadata,
+                _target: PhantomData,
+            }),
+            None => Err(ptr),
+        }
+    }
+
+    /// Gets a reference to the object as the base trait object `B`.
+    pub fn as_base(ptr: &Self) -> &B {
+        &*ptr.fat
+    }
+
+    /// Gets the fat pointer wrapped by a view, which can be used to perform further casts by reference.
+    pub fn as_fat(ptr: &Self) -> &DynDynFat<B, P> {
+        &ptr.fat
+    }
+
+    /// Gets the [`DynDynTable`] of the object referenced by a view without dereferencing it.
+    pub fn get_dyn_dyn_table(ptr: &Self) -> DynDynTable {
+        DynDynFat::get_dyn_dyn_table(&ptr.fat)
+    }
+
+    /// Unwraps a view, returning the fat pointer wrapping the original pointer to the object.
+    pub fn into_fat(ptr: Self) -> DynDynFat<B, P> {
+        ptr.fat
+    }
+}
+
+impl<B, D, P> DynDynView<B, D, P>
+where
+    B: ?Sized + DynDynBaseObject + 'static,
+    D: ?Sized + Pointee<Metadata = DynMetadata<D>> + 'static,
+    P: GetDynDynTable<B> + DerefMut,
+    P::Target: Unsize<B>,
+{
+    /// Gets a mutable reference to the object as the base trait object `B`.
+    pub fn as_base_mut(ptr: &mut Self) -> &mut B {
+        &mut *ptr.fat
+    }
+}
+
+impl<B, D, P> Deref for DynDynView<B, D, P>
+where
+    B: ?Sized + DynDynBaseObject,
+    D: ?Sized + Pointee<Metadata = DynMetadata<D>>,
+    P: Deref,
+    P::Target: Unsize<B>,
+{
+    type Target = D;
+
+    fn deref(&self) -> &D {
+        // SAFETY: The metadata was retrieved from the table cached by the fat pointer. Since B is a base trait object rather than a wrapper
+        //         around one, that table matches the object that the fat pointer dereferences to.
+        unsafe { &*ptr::from_raw_parts(&*self.fat as *const P::Target as *const (), self.metadata) }
+    }
+}
+
+impl<B, D, P> DerefMut for DynDynView<B, D, P>
+where
+    B: ?Sized + DynDynBaseObject,
+    D: ?Sized + Pointee<Metadata = DynMetadata<D>>,
+    P: DerefMut,
+    P::Target: Unsize<B>,
+{
+    fn deref_mut(&mut self) -> &mut D {
+        // SAFETY: See above.
+        unsafe {
+            &mut *ptr::from_raw_parts_mut(
+                &mut *self.fat as *mut P::Target as *mut (),
+                self.metadata,
+            )
+        }
+    }
+}
+
+// SAFETY: A view dereferences to the same object as the wrapped pointer with different metadata attached, so if that pointer is StableDeref
+//         then so is the view.
+unsafe impl<B, D, P> StableDeref for DynDynView<B, D, P>
+where
+    B: ?Sized + DynDynBaseObject,
+    D: ?Sized + Pointee<Metadata = DynMetadata<D>>,
+    P: StableDeref,
+    P::Target: Unsize<B>,
+{
+}
+
+impl<B, D, P> Clone for DynDynView<B, D, P>
+where
+    B: ?Sized + DynDynBaseObject,
+    D: ?Sized,
+    P: CloneStableDeref,
+{
+    fn clone(&self) -> Self {
+        DynDynView {
+            // SAFETY: Since P is CloneStableDeref, the clone dereferences to the same object, so the cached table and metadata still apply.
+            fat: unsafe { DynDynFat::clone_unchecked(&self.fat) },
+            metadata: self.metadata,
+            _target: PhantomData,
+        }
+    }
+}
+
+// SAFETY: See the Clone implementation above.
+unsafe impl<B, D, P> CloneStableDeref for DynDynView<B, D, P>
+where
+    B: ?Sized + DynDynBaseObject,
+    D: ?Sized + Pointee<Metadata = DynMetadata<D>>,
+    P: CloneStableDeref,
+    P::Target: Unsize<B>,
+{
+}
+
+impl<B, D, P> Debug for DynDynView<B, D, P>
+where
+    B: ?Sized + DynDynBaseObject,
+    D: ?Sized + Pointee<Metadata = DynMetadata<D>> + Debug,
+    P: Deref,
+    P::Target: Unsize<B>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl<B, D, P> Display for DynDynView<B, D, P>
+where
+    B: ?Sized + DynDynBaseObject,
+    D: ?Sized + Pointee<Metadata = DynMetadata<D>> + Display,
+    P: Deref,
+    P::Target: Unsize<B>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&**self, f)
+    }
+}
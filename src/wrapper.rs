@@ -0,0 +1,31 @@
+// Implementations of DynDynBase for standard library wrappers that can hold a base trait object as their unsized last field, e.g.
+// Cell<dyn Base>. These allow such wrappers to be cast using the outer struct syntax of dyn_dyn_cast!, e.g.
+// dyn_dyn_cast!(move Base => Trait [Cell<$>], rc). As with #[derive(DynDynBase)], the concrete type of the wrapped object is removed from
+// the table, since it isn't the concrete type of the wrapper.
+//
+// Retrieving the table requires calling a method on the wrapped object through a reference, since the only receivers that can be called
+// through a trait object without the arbitrary_self_types features are references and smart pointers. This means that there are no
+// implementations for wrappers that allow the object to be mutably borrowed while only holding a shared reference to the wrapper, i.e.
+// UnsafeCell, RefCell, Mutex and RwLock. Creating a shared reference to the object without borrowing or locking it could alias a live
+// mutable reference to it, while borrowing or locking it would panic or deadlock if it's already mutably borrowed or locked.
+
+use crate::{DynDynBase, DynDynTable};
+use core::cell::Cell;
+use core::mem::ManuallyDrop;
+
+// SAFETY: The table is passed through from the wrapped object, which has the same lifetime as the wrapper, with its concrete type removed.
+unsafe impl<B: ?Sized + DynDynBase> DynDynBase for ManuallyDrop<B> {
+    fn get_dyn_dyn_table(&self) -> DynDynTable {
+        B::get_dyn_dyn_table(&**self).without_concrete_type()
+    }
+}
+
+// SAFETY: The table is passed through from the wrapped object, which has the same lifetime as the wrapper, with its concrete type removed.
+unsafe impl<B: ?Sized + DynDynBase> DynDynBase for Cell<B> {
+    fn get_dyn_dyn_table(&self) -> DynDynTable {
+        // SAFETY: Since Cell<B> is not Sync and only provides a reference to its contents via Cell::get_mut, which takes a mutable reference
+        //         to the Cell, no mutable reference to the contents can be live while we hold a shared reference to the Cell. The reference
+        //         created here doesn't escape this function.
+        B::get_dyn_dyn_table(unsafe { &*self.as_ptr() }).without_concrete_type()
+    }
+}
@@ -0,0 +1,144 @@
+#![cfg(feature = "allocator-api")]
+#![feature(allocator_api)]
+
+use core::alloc::Layout;
+use core::cell::Cell;
+use core::pin::Pin;
+use core::ptr::NonNull;
+use dyn_dyn::{dyn_dyn_base, dyn_dyn_cast, dyn_dyn_impl};
+use std::alloc::{AllocError, Allocator, Global};
+use std::rc::Rc;
+use std::sync::Arc;
+
+#[derive(Clone, Copy)]
+struct Counting<'a>(&'a Cell<isize>);
+
+// SAFETY: All allocations are forwarded to the global allocator.
+unsafe impl Allocator for Counting<'_> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.0.set(self.0.get() + 1);
+        Global.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.0.set(self.0.get() - 1);
+
+        // SAFETY: The memory was allocated by the global allocator with this layout.
+        unsafe { Global.deallocate(ptr, layout) }
+    }
+}
+
+#[dyn_dyn_base]
+trait Subsystem {}
+
+trait Update {
+    fn update(&self) -> u32;
+}
+
+struct Physics(u32);
+struct Audio;
+
+#[dyn_dyn_impl(Update)]
+impl Subsystem for Physics {}
+
+#[dyn_dyn_impl]
+impl Subsystem for Audio {}
+
+impl Update for Physics {
+    fn update(&self) -> u32 {
+        self.0
+    }
+}
+
+#[test]
+fn test_box_in() {
+    let count = Cell::new(0);
+
+    let b: Box<dyn Subsystem, Counting> = Box::new_in(Physics(1), Counting(&count));
+    assert_eq!(1, count.get());
+
+    let b: Box<dyn Update, Counting> = dyn_dyn_cast!(move Subsystem => Update, b).ok().unwrap();
+    assert_eq!(1, b.update());
+    assert_eq!(1, count.get());
+
+    drop(b);
+    assert_eq!(0, count.get());
+}
+
+#[test]
+fn test_box_in_fail() {
+    let count = Cell::new(0);
+
+    let b: Box<dyn Subsystem, Counting> = Box::new_in(Audio, Counting(&count));
+    let b: Box<dyn Subsystem, Counting> = dyn_dyn_cast!(move Subsystem => Update, b).err().unwrap();
+
+    drop(b);
+    assert_eq!(0, count.get());
+}
+
+#[test]
+fn test_pin_box_in() {
+    // Pinning a Box requires its allocator to be 'static.
+    let count: &'static Cell<isize> = Box::leak(Box::new(Cell::new(0)));
+
+    let b: Pin<Box<dyn Subsystem, Counting>> = Box::pin_in(Physics(1), Counting(count));
+    assert_eq!(
+        1,
+        dyn_dyn_cast!(Subsystem => Update, &b)
+            .ok()
+            .unwrap()
+            .update()
+    );
+
+    let b: Pin<Box<dyn Update, Counting>> =
+        dyn_dyn_cast!(move Subsystem => Update, b).ok().unwrap();
+    assert_eq!(1, b.update());
+    assert_eq!(1, count.get());
+
+    drop(b);
+    assert_eq!(0, count.get());
+}
+
+#[test]
+fn test_box_in_ref() {
+    let count = Cell::new(0);
+    let b: Box<dyn Subsystem, Counting> = Box::new_in(Physics(2), Counting(&count));
+
+    assert_eq!(
+        2,
+        dyn_dyn_cast!(Subsystem => Update, &b)
+            .ok()
+            .unwrap()
+            .update()
+    );
+}
+
+#[test]
+fn test_rc_in() {
+    let count = Cell::new(0);
+
+    let rc: Rc<dyn Subsystem, Counting> = Rc::new_in(Physics(3), Counting(&count));
+    let weak = Rc::downgrade(&rc);
+
+    let rc: Rc<dyn Update, Counting> = dyn_dyn_cast!(move Subsystem => Update, rc).ok().unwrap();
+    assert_eq!(3, rc.update());
+
+    let weak = dyn_dyn_cast!(move Subsystem => Update, weak).ok().unwrap();
+    assert_eq!(3, weak.upgrade().unwrap().update());
+
+    drop(rc);
+    drop(weak);
+    assert_eq!(0, count.get());
+}
+
+#[test]
+fn test_arc_in() {
+    let count = Cell::new(0);
+
+    let arc: Arc<dyn Subsystem, Counting> = Arc::new_in(Physics(4), Counting(&count));
+    let arc: Arc<dyn Update, Counting> = dyn_dyn_cast!(move Subsystem => Update, arc).ok().unwrap();
+    assert_eq!(4, arc.update());
+
+    drop(arc);
+    assert_eq!(0, count.get());
+}
@@ -0,0 +1,101 @@
+use dyn_dyn::{dyn_dyn_base, dyn_dyn_cast, dyn_dyn_impl, DynDynFat};
+
+#[dyn_dyn_base]
+trait Base {}
+trait Trait {
+    fn test(&self) -> u32;
+}
+
+struct SendStruct(u32);
+
+#[dyn_dyn_impl(Trait, Trait + Send, Trait + Send + Sync)]
+impl Base for SendStruct {}
+impl Trait for SendStruct {
+    fn test(&self) -> u32 {
+        self.0
+    }
+}
+
+struct BareStruct(u32);
+
+#[dyn_dyn_impl(Trait)]
+impl Base for BareStruct {}
+impl Trait for BareStruct {
+    fn test(&self) -> u32 {
+        self.0
+    }
+}
+
+fn assert_send<T: ?Sized + Send>(_: &T) {}
+
+#[test]
+fn test_cast_ref_auto_traits() {
+    let s = &SendStruct(1) as &dyn Base;
+
+    assert_eq!(
+        Ok(1),
+        dyn_dyn_cast!(Base => Trait + Send, s)
+            .map(|t| {
+                assert_send(t);
+                t.test()
+            })
+            .map_err(|_| ())
+    );
+    assert_eq!(
+        Ok(1),
+        dyn_dyn_cast!(Base => Trait + Sync + Send, s)
+            .map(|t| t.test())
+            .map_err(|_| ())
+    );
+    assert!(dyn_dyn_cast!(Base => Trait + Sync, s).is_err());
+
+    let b = &BareStruct(2) as &dyn Base;
+
+    assert!(dyn_dyn_cast!(Base => Trait, b).is_ok());
+    assert!(dyn_dyn_cast!(Base => Trait + Send, b).is_err());
+}
+
+#[test]
+fn test_cast_mut_auto_traits() {
+    let s = &mut SendStruct(1) as &mut dyn Base;
+
+    assert_eq!(
+        Ok(1),
+        dyn_dyn_cast!(mut Base => Trait + Send, s)
+            .map(|t: &mut (dyn Trait + Send)| t.test())
+            .map_err(|_| ())
+    );
+}
+
+#[test]
+fn test_cast_fat_auto_traits() {
+    let s: DynDynFat<dyn Base, &dyn Base> = DynDynFat::new(&SendStruct(1));
+
+    assert_eq!(
+        Ok(1),
+        dyn_dyn_cast!(Base => Trait + Send + Sync, &s)
+            .map(|t| t.test())
+            .map_err(|_| ())
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_cast_alloc_auto_traits() {
+    use std::sync::Arc;
+    use std::thread;
+
+    let boxed: Box<dyn Base + Send> = Box::new(SendStruct(1));
+    let boxed: Box<dyn Trait + Send> = dyn_dyn_cast!(move Base => Trait + Send, boxed)
+        .ok()
+        .unwrap();
+
+    assert_eq!(1, thread::spawn(move || boxed.test()).join().unwrap());
+
+    let arc: Arc<dyn Base + Send + Sync> = Arc::new(SendStruct(2));
+    let arc: Arc<dyn Trait + Send + Sync> = dyn_dyn_cast!(move Base => Trait + Send + Sync, arc)
+        .ok()
+        .unwrap();
+
+    assert_eq!(2, thread::spawn(move || arc.test()).join().unwrap());
+}
@@ -250,6 +250,146 @@ fn test_multi_base() {
     assert!(dyn_dyn_cast!(BaseB => TraitB, &TestStruct as &dyn BaseB).is_ok());
 }
 
+#[test]
+fn test_cross_base() {
+    #[dyn_dyn_base]
+    trait BaseA {}
+
+    #[dyn_dyn_base]
+    trait BaseB {}
+    trait TraitB {}
+
+    struct TestStruct;
+
+    #[dyn_dyn_impl(BaseB)]
+    impl BaseA for TestStruct {}
+
+    #[dyn_dyn_impl(TraitB)]
+    impl BaseB for TestStruct {}
+    impl TraitB for TestStruct {}
+
+    let b = dyn_dyn_cast!(BaseA => BaseB, &TestStruct as &dyn BaseA);
+    assert!(b.is_ok());
+    assert!(dyn_dyn_cast!(BaseB => TraitB, b.ok().unwrap()).is_ok());
+
+    assert!(dyn_dyn_cast!(BaseA => BaseB => TraitB, &TestStruct as &dyn BaseA).is_ok());
+    assert!(dyn_dyn_cast!(mut BaseA => BaseB => TraitB, &mut TestStruct as &mut dyn BaseA).is_ok());
+}
+
+#[test]
+fn test_chained_cast_fails() {
+    #[dyn_dyn_base]
+    trait BaseA {}
+
+    #[dyn_dyn_base]
+    trait BaseB {}
+    trait TraitB {}
+
+    struct StructA;
+
+    #[dyn_dyn_impl]
+    impl BaseA for StructA {}
+
+    struct StructB;
+
+    #[dyn_dyn_impl(BaseB)]
+    impl BaseA for StructB {}
+
+    #[dyn_dyn_impl]
+    impl BaseB for StructB {}
+
+    let a = &StructA as &dyn BaseA;
+    let b = &StructB as &dyn BaseA;
+
+    assert_eq!(
+        Err(a as *const dyn BaseA as *const ()),
+        dyn_dyn_cast!(BaseA => BaseB => TraitB, a)
+            .map(|_| ())
+            .map_err(|a| a as *const dyn BaseA as *const ())
+    );
+    assert_eq!(
+        Err(b as *const dyn BaseA as *const ()),
+        dyn_dyn_cast!(BaseA => BaseB => TraitB, b)
+            .map(|_| ())
+            .map_err(|b| b as *const dyn BaseA as *const ())
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_chained_cast_alloc() {
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    #[dyn_dyn_base]
+    trait BaseA {}
+
+    #[dyn_dyn_base]
+    trait BaseB {}
+
+    #[dyn_dyn_base]
+    trait BaseC {}
+
+    trait TraitC {
+        fn test(&self) -> u32;
+    }
+
+    struct TestStruct(u32);
+
+    #[dyn_dyn_impl(BaseB)]
+    impl BaseA for TestStruct {}
+
+    #[dyn_dyn_impl(BaseC)]
+    impl BaseB for TestStruct {}
+
+    #[dyn_dyn_impl(TraitC)]
+    impl BaseC for TestStruct {}
+
+    impl TraitC for TestStruct {
+        fn test(&self) -> u32 {
+            self.0
+        }
+    }
+
+    let mut boxed: Box<dyn BaseA> = Box::new(TestStruct(1));
+
+    assert_eq!(
+        Ok(1),
+        dyn_dyn_cast!(BaseA => BaseB => BaseC => TraitC, &boxed)
+            .map(|t| t.test())
+            .map_err(|_| ())
+    );
+    assert_eq!(
+        Ok(1),
+        dyn_dyn_cast!(mut BaseA => BaseB => BaseC => TraitC, &mut boxed)
+            .map(|t| t.test())
+            .map_err(|_| ())
+    );
+    assert_eq!(
+        Ok(1),
+        dyn_dyn_cast!(move BaseA => BaseB => BaseC => TraitC, boxed)
+            .map(|t: Box<dyn TraitC>| t.test())
+            .map_err(|_| ())
+    );
+
+    let rc: Rc<dyn BaseA> = Rc::new(TestStruct(2));
+    assert!(dyn_dyn_cast!(move BaseA => BaseC => TraitC, rc.clone()).is_err());
+    assert_eq!(
+        Ok(2),
+        dyn_dyn_cast!(move BaseA => BaseB => BaseC => TraitC, rc)
+            .map(|t: Rc<dyn TraitC>| t.test())
+            .map_err(|_| ())
+    );
+
+    let arc: Arc<dyn BaseA> = Arc::new(TestStruct(3));
+    assert_eq!(
+        Ok(3),
+        dyn_dyn_cast!(move BaseA => BaseB => BaseC => TraitC, arc)
+            .map(|t: Arc<dyn TraitC>| t.test())
+            .map_err(|_| ())
+    );
+}
+
 #[test]
 fn test_temporaries_extended() {
     #[dyn_dyn_base]
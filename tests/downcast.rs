@@ -0,0 +1,81 @@
+use dyn_dyn::{dyn_dyn_base, dyn_dyn_downcast, dyn_dyn_impl};
+
+#[dyn_dyn_base]
+trait Base {}
+trait Trait {}
+
+#[derive(Debug, PartialEq, Eq)]
+struct StructA(u32);
+
+#[dyn_dyn_impl(Trait)]
+impl Base for StructA {}
+impl Trait for StructA {}
+
+#[derive(Debug, PartialEq, Eq)]
+struct StructB(u32);
+
+#[dyn_dyn_impl]
+impl Base for StructB {}
+
+#[test]
+fn test_downcast_ref() {
+    let a = StructA(1);
+    let b = StructB(2);
+
+    assert_eq!(
+        Ok(&a as *const _),
+        dyn_dyn_downcast!(Base => StructA, &a as &dyn Base)
+            .map(|a| a as *const _)
+            .map_err(|_| ())
+    );
+    assert!(dyn_dyn_downcast!(Base => StructB, &a as &dyn Base).is_err());
+
+    assert!(dyn_dyn_downcast!(Base => StructA, &b as &dyn Base).is_err());
+    assert_eq!(
+        Ok(&StructB(2)),
+        dyn_dyn_downcast!(Base => StructB, &b as &dyn Base).map_err(|_| ())
+    );
+}
+
+#[test]
+fn test_downcast_mut() {
+    let mut a = StructA(1);
+
+    if let Ok(a) = dyn_dyn_downcast!(mut Base => StructA, &mut a as &mut dyn Base) {
+        a.0 = 2;
+    }
+
+    assert_eq!(StructA(2), a);
+    assert!(dyn_dyn_downcast!(mut Base => StructB, &mut a as &mut dyn Base).is_err());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_downcast_alloc() {
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    let a: Box<dyn Base> = Box::new(StructA(1));
+    let a = dyn_dyn_downcast!(move Base => StructB, a).unwrap_err();
+    assert_eq!(
+        Ok(StructA(1)),
+        dyn_dyn_downcast!(move Base => StructA, a)
+            .map(|a| *a)
+            .map_err(|_| ())
+    );
+
+    let mut b = Box::new(StructB(2));
+    assert!(dyn_dyn_downcast!(Base => StructB, &b).is_ok());
+    assert!(dyn_dyn_downcast!(mut Base => StructB, &mut b).is_ok());
+
+    let a: Rc<dyn Base> = Rc::new(StructA(3));
+    assert_eq!(
+        Ok(&StructA(3)),
+        dyn_dyn_downcast!(move Base => StructA, a)
+            .as_deref()
+            .map_err(|_| ())
+    );
+
+    let b: Arc<dyn Base> = Arc::new(StructB(4));
+    assert!(dyn_dyn_downcast!(move Base => StructA, b).is_err());
+}
@@ -1,4 +1,6 @@
-use dyn_dyn::{dyn_dyn_base, dyn_dyn_cast, dyn_dyn_impl, DynDynBase, DynDynTable};
+use dyn_dyn::{
+    dyn_dyn_base, dyn_dyn_cast, dyn_dyn_downcast, dyn_dyn_impl, DynDynBase, DynDynTable,
+};
 
 #[dyn_dyn_base]
 trait Base {}
@@ -26,7 +28,7 @@ struct DstStruct<T: ?Sized>(u32, T);
 
 unsafe impl<B: ?Sized + DynDynBase> DynDynBase for DstStruct<B> {
     fn get_dyn_dyn_table(&self) -> DynDynTable {
-        B::get_dyn_dyn_table(&self.1)
+        B::get_dyn_dyn_table(&self.1).without_concrete_type()
     }
 }
 
@@ -45,3 +47,74 @@ fn test_dst_struct_cast() {
             .map_err(|_| ())
     );
 }
+
+#[derive(DynDynBase)]
+struct Envelope<P: ?Sized> {
+    id: u32,
+    payload: P,
+}
+
+#[derive(DynDynBase)]
+struct TupleEnvelope<'a, P: ?Sized>(&'a str, P)
+where
+    P: 'a;
+
+#[test]
+fn test_dst_struct_derive() {
+    let a: &Envelope<dyn Base> = &Envelope {
+        id: 1,
+        payload: StructA,
+    };
+    let b: &Envelope<dyn Base> = &Envelope {
+        id: 2,
+        payload: StructB(1234),
+    };
+
+    assert!(dyn_dyn_cast!(Base => Trait [Envelope<$>], a).is_err());
+
+    let b: &Envelope<dyn Trait> = dyn_dyn_cast!(Base => Trait [Envelope<$>], b).ok().unwrap();
+    assert_eq!(2, b.id);
+    assert_eq!(1234, b.payload.test());
+}
+
+#[test]
+fn test_dst_struct_derive_concrete_type() {
+    let a: &Envelope<dyn Base> = &Envelope {
+        id: 1,
+        payload: StructA,
+    };
+    let table = dyn_dyn::introspect::<Envelope<dyn Base>>(a);
+
+    // The table of the payload records StructA, but the concrete type of the envelope is Envelope<StructA>, which can't be recorded.
+    assert!(!table.is::<StructA>());
+    assert!(!table.is::<Envelope<StructA>>());
+    assert!(!DynDynTable::same_concrete_type(&table, &table));
+    assert!(dyn_dyn_downcast!(Base => StructA, &a.payload as &dyn Base).is_ok());
+}
+
+#[test]
+fn test_dst_struct_derive_tuple() {
+    let e: &TupleEnvelope<'static, dyn Base> = &TupleEnvelope("e", StructB(5));
+
+    let e: &TupleEnvelope<'static, dyn Trait> =
+        dyn_dyn_cast!(Base => Trait [TupleEnvelope<'static, $>], e)
+            .ok()
+            .unwrap();
+    assert_eq!("e", e.0);
+    assert_eq!(5, e.1.test());
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_dst_struct_derive_box() {
+    let e: Box<Envelope<dyn Base>> = Box::new(Envelope {
+        id: 3,
+        payload: StructB(6),
+    });
+
+    let e: Box<Envelope<dyn Trait>> = dyn_dyn_cast!(move Base => Trait [Envelope<$>], e)
+        .ok()
+        .unwrap();
+    assert_eq!(3, e.id);
+    assert_eq!(6, e.payload.test());
+}
@@ -0,0 +1,145 @@
+#![cfg(feature = "std")]
+
+use dyn_dyn::{dyn_dyn_base, dyn_dyn_cast, dyn_dyn_downcast, dyn_dyn_impl, DynDynCastError};
+use std::any::TypeId;
+
+#[dyn_dyn_base]
+trait Base {
+    fn id(&self) -> u32;
+}
+
+trait TestTrait {
+    fn test(&self) -> u32;
+}
+
+trait OtherTrait {}
+
+struct TestStruct(u32);
+
+#[dyn_dyn_impl(TestTrait)]
+impl Base for TestStruct {
+    fn id(&self) -> u32 {
+        self.0
+    }
+}
+
+impl TestTrait for TestStruct {
+    fn test(&self) -> u32 {
+        self.0
+    }
+}
+
+#[test]
+fn test_error_ref() {
+    let b: &dyn Base = &TestStruct(1234);
+
+    assert_eq!(
+        Some(1234),
+        dyn_dyn_cast!(try Base => TestTrait, b)
+            .ok()
+            .map(|t| t.test())
+    );
+
+    let err = dyn_dyn_cast!(try Base => OtherTrait, b)
+        .map(|_| ())
+        .unwrap_err();
+
+    assert_eq!(TypeId::of::<dyn OtherTrait>(), err.target_type_id());
+    assert_eq!(TypeId::of::<TestStruct>(), err.concrete_type_id());
+    assert!(err.target_type_name().contains("OtherTrait"));
+    assert_eq!(1234, err.into_inner().id());
+}
+
+#[test]
+fn test_error_mut() {
+    let mut s = TestStruct(1234);
+
+    let err = dyn_dyn_cast!(try mut Base => OtherTrait, &mut s)
+        .map(|_| ())
+        .unwrap_err();
+
+    assert_eq!(1234, err.get_ref().id());
+    assert!(dyn_dyn_cast!(try mut Base => TestTrait, &mut s).is_ok());
+}
+
+#[test]
+fn test_error_chain() {
+    #[dyn_dyn_base]
+    trait OtherBase {}
+
+    let b: &dyn Base = &TestStruct(1234);
+    let err = dyn_dyn_cast!(try Base => OtherBase => TestTrait, b)
+        .map(|_| ())
+        .unwrap_err();
+
+    assert_eq!(TypeId::of::<dyn TestTrait>(), err.target_type_id());
+    assert_eq!(TypeId::of::<TestStruct>(), err.concrete_type_id());
+}
+
+#[test]
+fn test_error_downcast() {
+    struct OtherStruct;
+
+    let b: &dyn Base = &TestStruct(1234);
+    let err = dyn_dyn_downcast!(try Base => OtherStruct, b)
+        .map(|_| ())
+        .unwrap_err();
+
+    assert_eq!(TypeId::of::<OtherStruct>(), err.target_type_id());
+    assert!(dyn_dyn_downcast!(try Base => TestStruct, b).is_ok());
+}
+
+#[test]
+fn test_error_lifetime_target() {
+    #[dyn_dyn_base]
+    trait Deserializer<'de> {}
+    trait Visitor<'de> {}
+
+    struct Empty;
+
+    #[dyn_dyn_impl]
+    impl<'de> Deserializer<'de> for Empty {}
+
+    fn visitor<'a, 'de>(
+        d: &'a dyn Deserializer<'de>,
+    ) -> Result<&'a dyn Visitor<'de>, DynDynCastError<&'a dyn Deserializer<'de>>> {
+        dyn_dyn_cast!(try Deserializer<'de> => Visitor<'de>, d)
+    }
+
+    let err = visitor(&Empty).map(|_| ()).unwrap_err();
+
+    assert_eq!(TypeId::of::<dyn Visitor<'static>>(), err.target_type_id());
+}
+
+#[test]
+#[cfg(feature = "dynamic-names")]
+fn test_error_display() {
+    let b: &dyn Base = &TestStruct(1234);
+    let err = dyn_dyn_cast!(try Base => OtherTrait, b)
+        .map(|_| ())
+        .unwrap_err();
+
+    assert!(err.concrete_type_name().contains("TestStruct"));
+    assert!(err.to_string().contains("TestStruct"));
+    assert!(err.to_string().contains("OtherTrait"));
+}
+
+#[test]
+fn test_error_propagate() {
+    fn cast(b: Box<dyn Base>) -> Result<u32, Box<dyn std::error::Error>> {
+        Ok(dyn_dyn_cast!(try move Base => TestTrait, b)?.test())
+    }
+
+    fn cast_other(b: Box<dyn Base>) -> Result<(), Box<dyn std::error::Error>> {
+        dyn_dyn_cast!(try move Base => OtherTrait, b)?;
+        Ok(())
+    }
+
+    assert_eq!(1234, cast(Box::new(TestStruct(1234))).unwrap());
+
+    let err = cast_other(Box::new(TestStruct(1234))).unwrap_err();
+    assert!(err.to_string().contains("OtherTrait"));
+    assert!(err
+        .downcast::<DynDynCastError<Box<dyn Base>>>()
+        .is_ok_and(|err| err.into_inner().id() == 1234));
+}
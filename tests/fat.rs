@@ -5,7 +5,10 @@ extern crate alloc;
 use alloc::rc::Rc;
 use core::cell::Cell;
 use core::ops::Deref;
-use dyn_dyn::{dyn_dyn_cast, DynDynBase, DynDynFat, DynDynTable, DynDynTableEntry, GetDynDynTable};
+use dyn_dyn::{
+    dyn_dyn_base, dyn_dyn_cast, dyn_dyn_impl, DynDynBase, DynDynFat, DynDynTable, DynDynTableEntry,
+    GetDynDynTable,
+};
 use stable_deref_trait::StableDeref;
 
 // We need the pointers to these two tables to be distinct in order to properly differentiate them, so these cannot be declared as
@@ -30,7 +33,7 @@ struct TestStruct<'a>(&'a Cell<usize>, &'static [DynDynTableEntry]);
 impl<'a> Base for TestStruct<'a> {
     fn get_dyn_dyn_table(&self) -> DynDynTable {
         self.0.set(self.0.get() + 1);
-        DynDynTable::new(self.1)
+        DynDynTable::new_lifetime_erased::<TestStruct<'static>>(self.1)
     }
 }
 
@@ -118,3 +121,85 @@ fn test_clone_changes_table() {
         DynDynFat::get_dyn_dyn_table(&ptr).into_slice() as *const _
     );
 }
+
+#[dyn_dyn_base]
+trait Component {}
+
+#[dyn_dyn_base]
+trait Renderable {
+    fn frame(&self) -> u32;
+}
+
+trait Tick {
+    fn tick(&mut self);
+}
+
+struct Sprite(u32);
+
+#[dyn_dyn_impl(Renderable, Tick)]
+impl Component for Sprite {}
+
+#[dyn_dyn_impl(Tick)]
+impl Renderable for Sprite {
+    fn frame(&self) -> u32 {
+        self.0
+    }
+}
+
+impl Tick for Sprite {
+    fn tick(&mut self) {
+        self.0 += 1;
+    }
+}
+
+struct Inert;
+
+#[dyn_dyn_impl]
+impl Component for Inert {}
+
+#[test]
+fn test_cast_methods() {
+    let mut fat: DynDynFat<dyn Component, Box<dyn Component>> = DynDynFat::new(Box::new(Sprite(0)));
+
+    DynDynFat::cast_mut::<dyn Tick>(&mut fat)
+        .ok()
+        .unwrap()
+        .tick();
+    assert_eq!(
+        1,
+        DynDynFat::cast_ref::<dyn Renderable>(&fat)
+            .ok()
+            .unwrap()
+            .frame()
+    );
+
+    let mut boxed: Box<dyn Tick> = DynDynFat::cast(fat).ok().unwrap();
+    boxed.tick();
+
+    let inert: DynDynFat<dyn Component, Box<dyn Component>> = DynDynFat::new(Box::new(Inert));
+    assert!(DynDynFat::cast_ref::<dyn Tick>(&inert).is_err());
+    assert!(DynDynFat::cast::<dyn Tick>(inert).is_err());
+}
+
+#[test]
+fn test_cast_fat() {
+    let fat: DynDynFat<dyn Component, Rc<dyn Component>> = DynDynFat::new(Rc::new(Sprite(5)));
+
+    let by_ref: DynDynFat<dyn Renderable, &dyn Renderable> =
+        DynDynFat::cast_fat(DynDynFat::deref_fat(&fat))
+            .ok()
+            .unwrap();
+    assert_eq!(5, by_ref.frame());
+
+    let renderable: DynDynFat<dyn Renderable, Rc<dyn Renderable>> =
+        DynDynFat::cast_fat(fat).ok().unwrap();
+    assert_eq!(5, renderable.frame());
+    assert_eq!(
+        dyn_dyn::introspect::<dyn Renderable>(&Sprite(0)).into_slice() as *const _,
+        DynDynFat::get_dyn_dyn_table(&renderable).into_slice() as *const _
+    );
+    assert!(DynDynFat::cast_ref::<dyn Tick>(&renderable).is_ok());
+
+    let inert: DynDynFat<dyn Component, Rc<dyn Component>> = DynDynFat::new(Rc::new(Inert));
+    assert!(DynDynFat::cast_fat::<dyn Renderable>(inert).is_err());
+}
@@ -1,4 +1,4 @@
-use dyn_dyn::{dyn_dyn_base, dyn_dyn_cast, dyn_dyn_impl};
+use dyn_dyn::{dyn_dyn_base, dyn_dyn_cast, dyn_dyn_downcast, dyn_dyn_impl, DynDynTable};
 
 #[test]
 fn test_generic_base() {
@@ -227,4 +227,121 @@ fn test_where_clause_on_derived() {
             .map(|b| b.test())
             .map_err(|_| ())
     );
+
+    assert!(dyn_dyn_downcast!(Base => TestStruct<u32>, &TestStruct(0_u32) as &dyn Base).is_ok());
+    assert!(dyn_dyn_downcast!(Base => TestStruct<u64>, &TestStruct(0_u32) as &dyn Base).is_err());
+}
+
+#[test]
+fn test_generic_implementor_without_static_bound() {
+    #[dyn_dyn_base]
+    trait Base {
+        fn len(&self) -> usize;
+    }
+
+    trait TestTrait {}
+
+    struct TestStruct<T>(T);
+
+    // Exposing any traits would require T: 'static, but an impl that exposes none can still be used with borrowed types. Since T isn't
+    // known to be 'static, the table records no concrete type that could be downcast into.
+    #[dyn_dyn_impl]
+    impl<T: AsRef<str>> Base for TestStruct<T> {
+        fn len(&self) -> usize {
+            self.0.as_ref().len()
+        }
+    }
+
+    let s = String::from("abc");
+    let borrowed = TestStruct(&s);
+    let borrowed = &borrowed as &dyn Base;
+
+    assert_eq!(3, borrowed.len());
+    assert!(dyn_dyn_cast!(Base => TestTrait, borrowed).is_err());
+
+    let owned = TestStruct("abcd");
+    let table = dyn_dyn::introspect::<dyn Base>(&owned);
+
+    assert!(!table.is::<TestStruct<&'static str>>());
+    assert!(!DynDynTable::same_concrete_type(&table, &table));
+    assert!(dyn_dyn_downcast!(Base => TestStruct<&'static str>, &owned as &dyn Base).is_err());
+}
+
+#[test]
+fn test_cast_fn_generic_base() {
+    use dyn_dyn::{DowncastUnchecked, DynDyn, DynDynBaseObject};
+
+    #[dyn_dyn_base]
+    trait BaseA {}
+    #[dyn_dyn_base]
+    trait BaseB {}
+    trait TestTrait {
+        fn test(&self) -> u32;
+    }
+
+    struct TestStruct;
+
+    #[dyn_dyn_impl(TestTrait)]
+    impl BaseA for TestStruct {}
+    #[dyn_dyn_impl]
+    impl BaseB for TestStruct {}
+    impl TestTrait for TestStruct {
+        fn test(&self) -> u32 {
+            1234
+        }
+    }
+
+    fn cast_test<'a, B: ?Sized + DynDynBaseObject + 'static, P: DynDyn<'a, B>>(
+        p: P,
+    ) -> Result<<P as DowncastUnchecked<'a>>::DowncastResult<dyn TestTrait>, P> {
+        dyn_dyn::cast::<B, dyn TestTrait, _>(p)
+    }
+
+    assert_eq!(
+        Ok(1234),
+        cast_test::<dyn BaseA, _>(&TestStruct as &dyn BaseA)
+            .map(|t| t.test())
+            .map_err(|_| ())
+    );
+    assert!(cast_test::<dyn BaseB, _>(&TestStruct as &dyn BaseB).is_err());
+
+    let mut s = TestStruct;
+    assert_eq!(
+        Ok(1234),
+        cast_test::<dyn BaseA, _>(&mut s as &mut dyn BaseA)
+            .map(|t| t.test())
+            .map_err(|_| ())
+    );
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_cast_fn_returns_original() {
+    use dyn_dyn::DynDynRef;
+
+    #[dyn_dyn_base]
+    trait Base {
+        fn id(&self) -> u32;
+    }
+    trait TestTrait {}
+
+    struct TestStruct(u32);
+
+    #[dyn_dyn_impl]
+    impl Base for TestStruct {
+        fn id(&self) -> u32 {
+            self.0
+        }
+    }
+
+    let b: Box<dyn Base> = Box::new(TestStruct(5));
+
+    assert!(dyn_dyn::cast::<dyn Base, dyn TestTrait, _>(DynDynRef::new(&b)).is_err());
+    assert_eq!(
+        5,
+        dyn_dyn::cast::<dyn Base, dyn TestTrait, _>(b)
+            .map(|_| ())
+            .unwrap_err()
+            .id()
+    );
 }
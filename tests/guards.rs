@@ -0,0 +1,145 @@
+#![cfg_attr(feature = "std", feature(mapped_lock_guards))]
+
+use core::cell::{Ref, RefCell, RefMut};
+use dyn_dyn::{dyn_dyn_base, dyn_dyn_cast, dyn_dyn_impl};
+
+#[dyn_dyn_base]
+trait Widget {}
+
+trait Text {
+    fn text(&self) -> &str;
+    fn set_text(&mut self, text: &'static str);
+}
+
+struct Label(&'static str);
+struct Spacer;
+
+#[dyn_dyn_impl(Text)]
+impl Widget for Label {}
+
+#[dyn_dyn_impl]
+impl Widget for Spacer {}
+
+impl Text for Label {
+    fn text(&self) -> &str {
+        self.0
+    }
+
+    fn set_text(&mut self, text: &'static str) {
+        self.0 = text;
+    }
+}
+
+#[test]
+fn test_ref() {
+    let cell = RefCell::new(Label("a"));
+    let cell: &RefCell<dyn Widget> = &cell;
+
+    let text: Ref<dyn Text> = dyn_dyn_cast!(move Widget => Text, cell.borrow())
+        .ok()
+        .unwrap();
+    assert_eq!("a", text.text());
+
+    // The borrow is still held by the cast guard.
+    assert!(cell.try_borrow_mut().is_err());
+    drop(text);
+    assert!(cell.try_borrow_mut().is_ok());
+}
+
+#[test]
+fn test_ref_mut() {
+    let cell = RefCell::new(Label("a"));
+    let cell: &RefCell<dyn Widget> = &cell;
+
+    let mut text: RefMut<dyn Text> = dyn_dyn_cast!(move Widget => Text, cell.borrow_mut())
+        .ok()
+        .unwrap();
+    text.set_text("b");
+    drop(text);
+
+    assert_eq!(
+        "b",
+        dyn_dyn_cast!(move Widget => Text, cell.borrow())
+            .ok()
+            .unwrap()
+            .text()
+    );
+}
+
+#[test]
+fn test_ref_fail() {
+    let cell = RefCell::new(Spacer);
+    let cell: &RefCell<dyn Widget> = &cell;
+
+    let guard: Ref<dyn Widget> = dyn_dyn_cast!(move Widget => Text, cell.borrow())
+        .err()
+        .unwrap();
+    assert!(cell.try_borrow_mut().is_err());
+    drop(guard);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_rc_ref_cell() {
+    use std::rc::Rc;
+
+    let widget: Rc<RefCell<dyn Widget>> = Rc::new(RefCell::new(Label("a")));
+
+    dyn_dyn_cast!(move Widget => Text, widget.borrow_mut())
+        .ok()
+        .unwrap()
+        .set_text("b");
+    assert_eq!(
+        "b",
+        dyn_dyn_cast!(move Widget => Text, widget.borrow())
+            .ok()
+            .unwrap()
+            .text()
+    );
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_mutex() {
+    use std::sync::{MappedMutexGuard, Mutex};
+
+    let mutex: Box<Mutex<dyn Widget>> = Box::new(Mutex::new(Label("a")));
+
+    let mut text: MappedMutexGuard<dyn Text> =
+        dyn_dyn_cast!(move Widget => Text, mutex.lock().unwrap())
+            .ok()
+            .unwrap();
+    text.set_text("b");
+    assert!(mutex.try_lock().is_err());
+    drop(text);
+
+    assert_eq!(
+        "b",
+        dyn_dyn_cast!(move Widget => Text, mutex.lock().unwrap())
+            .ok()
+            .unwrap()
+            .text()
+    );
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_rw_lock() {
+    use std::sync::{MappedRwLockReadGuard, MappedRwLockWriteGuard, RwLock};
+
+    let lock: Box<RwLock<dyn Widget>> = Box::new(RwLock::new(Label("a")));
+
+    let mut text: MappedRwLockWriteGuard<dyn Text> =
+        dyn_dyn_cast!(move Widget => Text, lock.write().unwrap())
+            .ok()
+            .unwrap();
+    text.set_text("b");
+    drop(text);
+
+    let text: MappedRwLockReadGuard<dyn Text> =
+        dyn_dyn_cast!(move Widget => Text, lock.read().unwrap())
+            .ok()
+            .unwrap();
+    assert_eq!("b", text.text());
+    assert!(lock.try_write().is_err());
+}
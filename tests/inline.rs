@@ -0,0 +1,191 @@
+use core::cell::Cell;
+use core::fmt::Debug;
+use dyn_dyn::{
+    dyn_dyn_base, dyn_dyn_cast, dyn_dyn_impl, DynDynBase, DynDynInline, DynDynTable,
+    DynDynTableEntry,
+};
+
+static EMPTY_TABLE: (u8, [DynDynTableEntry; 0]) = (0, []);
+
+trait CountingBase {
+    fn get_dyn_dyn_table(&self) -> DynDynTable;
+}
+
+// SAFETY: This is for testing purposes only, so we rely on internal implementation details that get around the safety requirements
+unsafe impl<'a> DynDynBase for dyn CountingBase + 'a {
+    fn get_dyn_dyn_table(&self) -> DynDynTable {
+        self.get_dyn_dyn_table()
+    }
+}
+
+struct CountingStruct<'a>(&'a Cell<usize>);
+
+impl<'a> CountingBase for CountingStruct<'a> {
+    fn get_dyn_dyn_table(&self) -> DynDynTable {
+        self.0.set(self.0.get() + 1);
+        DynDynTable::new_lifetime_erased::<CountingStruct<'static>>(&EMPTY_TABLE.1)
+    }
+}
+
+#[dyn_dyn_base]
+trait Base {
+    fn get(&self) -> u32;
+}
+
+trait Trait {
+    fn set(&mut self, val: u32);
+}
+
+#[derive(Debug)]
+struct TestStruct<'a>(u32, &'a Cell<usize>);
+
+#[dyn_dyn_impl(Trait, Debug)]
+impl<'a> Base for TestStruct<'a> {
+    fn get(&self) -> u32 {
+        self.0
+    }
+}
+
+impl<'a> Trait for TestStruct<'a> {
+    fn set(&mut self, val: u32) {
+        self.0 = val;
+    }
+}
+
+impl<'a> Drop for TestStruct<'a> {
+    fn drop(&mut self) {
+        self.1.set(self.1.get() + 1);
+    }
+}
+
+#[repr(align(16))]
+struct Aligned(u32);
+
+#[dyn_dyn_impl]
+impl Base for Aligned {
+    fn get(&self) -> u32 {
+        self.0
+    }
+}
+
+struct Zst;
+
+#[dyn_dyn_impl]
+impl Base for Zst {
+    fn get(&self) -> u32 {
+        7
+    }
+}
+
+#[test]
+fn test_get_table_cached() {
+    let num_table_calls = Cell::new(0);
+    let mut obj: DynDynInline<dyn CountingBase, 8> =
+        DynDynInline::new(CountingStruct(&num_table_calls));
+
+    assert_eq!(0, num_table_calls.get());
+    assert_eq!(
+        &EMPTY_TABLE.1[..] as *const _,
+        DynDynInline::get_dyn_dyn_table(&obj).into_slice() as *const _
+    );
+    assert_eq!(1, num_table_calls.get());
+    let _ = dyn_dyn_cast!(CountingBase => CountingBase, &obj);
+    let _ = dyn_dyn_cast!(mut CountingBase => CountingBase, &mut obj);
+    assert_eq!(1, num_table_calls.get());
+}
+
+#[test]
+fn test_cast() {
+    let drops = Cell::new(0);
+    let mut obj: DynDynInline<dyn Base, 16> = DynDynInline::new(TestStruct(1, &drops));
+
+    dyn_dyn_cast!(mut Base => Trait, &mut obj)
+        .ok()
+        .unwrap()
+        .set(2);
+    assert_eq!(2, obj.get());
+
+    // Moving the container moves the object along with it, which must not invalidate the cached metadata.
+    let moved = obj;
+    assert_eq!(
+        "TestStruct(2, Cell { value: 0 })",
+        format!("{:?}", dyn_dyn_cast!(Base => Debug, &moved).ok().unwrap())
+    );
+
+    drop(moved);
+    assert_eq!(1, drops.get());
+}
+
+#[test]
+fn test_array() {
+    let drops = Cell::new(0);
+    let objs: [DynDynInline<dyn Base, 16>; 3] = [
+        DynDynInline::new(TestStruct(1, &drops)),
+        DynDynInline::new(Aligned(2)),
+        DynDynInline::new(Zst),
+    ];
+
+    assert_eq!(
+        vec![1, 2, 7],
+        objs.iter().map(|o| o.get()).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        vec![true, false, false],
+        objs.iter()
+            .map(|o| dyn_dyn_cast!(Base => Trait, o).is_ok())
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(0, (&*objs[1] as *const dyn Base).cast::<u8>() as usize % 16);
+
+    drop(objs);
+    assert_eq!(1, drops.get());
+}
+
+thread_local! {
+    static ZST: DynDynInline<dyn Base, 16> = const { DynDynInline::new(Zst) };
+}
+
+#[test]
+fn test_const() {
+    ZST.with(|obj| {
+        assert_eq!(7, obj.get());
+        assert!(dyn_dyn_cast!(Base => Trait, obj).is_err());
+        assert!(DynDynTable::same_concrete_type(
+            &DynDynInline::get_dyn_dyn_table(obj),
+            &DynDynInline::get_dyn_dyn_table(&DynDynInline::<dyn Base, 16>::new(Zst))
+        ));
+    });
+}
+
+#[dyn_dyn_base]
+trait SendBase: Send {
+    fn get(&self) -> u32;
+}
+
+impl Trait for Aligned {
+    fn set(&mut self, val: u32) {
+        self.0 = val;
+    }
+}
+
+#[dyn_dyn_impl(Trait)]
+impl SendBase for Aligned {
+    fn get(&self) -> u32 {
+        self.0
+    }
+}
+
+#[test]
+fn test_send() {
+    let mut obj: DynDynInline<dyn SendBase, 16> = DynDynInline::new(Aligned(3));
+
+    let obj = std::thread::spawn(move || {
+        if let Ok(obj) = dyn_dyn_cast!(mut SendBase => Trait, &mut obj) {
+            obj.set(4);
+        }
+        obj
+    })
+    .join()
+    .unwrap();
+    assert_eq!(4, obj.get());
+}
@@ -0,0 +1,398 @@
+use core::any::TypeId;
+use core::fmt;
+use core::marker::PhantomData;
+use dyn_dyn::{dyn_dyn_base, dyn_dyn_cast, dyn_dyn_downcast, dyn_dyn_impl, GetDynDynTable};
+
+#[dyn_dyn_base]
+trait Node<'src> {
+    fn text(&self) -> &'src str;
+}
+
+trait Leaf {
+    fn len(&self) -> usize;
+}
+
+struct Token;
+
+#[dyn_dyn_impl(Leaf)]
+impl<'src> Node<'src> for Token {
+    fn text(&self) -> &'src str {
+        "token"
+    }
+}
+
+impl Leaf for Token {
+    fn len(&self) -> usize {
+        5
+    }
+}
+
+struct Group;
+
+#[dyn_dyn_impl]
+impl<'src> Node<'src> for Group {
+    fn text(&self) -> &'src str {
+        "group"
+    }
+}
+
+fn leaf_len<'src>(node: &dyn Node<'src>) -> Option<usize> {
+    dyn_dyn_cast!(Node<'src> => Leaf, node)
+        .ok()
+        .map(|l| l.len())
+}
+
+#[test]
+fn test_lifetime_base() {
+    assert_eq!(Some(5), leaf_len(&Token));
+    assert_eq!(None, leaf_len(&Group));
+
+    assert!(dyn_dyn_cast!(Node<'_> => Leaf, &Token as &dyn Node).is_ok());
+    assert!(dyn_dyn_cast!(mut Node<'_> => Leaf, &mut Group as &mut dyn Node).is_err());
+}
+
+#[test]
+fn test_lifetime_base_returns_original() {
+    fn cast<'a, 'src>(node: &'a dyn Node<'src>) -> Result<&'a dyn Leaf, &'a dyn Node<'src>> {
+        dyn_dyn_cast!(Node<'src> => Leaf, node)
+    }
+
+    assert_eq!(Some("group"), cast(&Group).err().map(|n| n.text()));
+}
+
+#[test]
+fn test_lifetime_base_downcast() {
+    fn downcast<'a, 'src>(node: &'a dyn Node<'src>) -> Option<&'a Token> {
+        dyn_dyn_downcast!(Node<'src> => Token, node).ok()
+    }
+
+    assert!(downcast(&Token).is_some());
+    assert!(downcast(&Group).is_none());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_lifetime_base_alloc() {
+    fn cast<'src>(
+        node: Box<dyn Node<'src> + 'src>,
+    ) -> Result<Box<dyn Leaf + 'src>, Box<dyn Node<'src> + 'src>> {
+        dyn_dyn_cast!(move Node<'src> => Leaf, node)
+    }
+
+    assert!(cast(Box::new(Token)).is_ok());
+    assert!(cast(Box::new(Group)).is_err());
+}
+
+#[dyn_dyn_base]
+trait Base {}
+
+struct StrView<'a>(&'a str);
+
+#[dyn_dyn_impl(fmt::Display)]
+impl<'a> Base for StrView<'a> {}
+
+impl<'a> fmt::Display for StrView<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+struct Pair<'a, 'b>(&'a str, &'b str);
+
+#[dyn_dyn_impl(fmt::Display)]
+impl Base for Pair<'_, '_> {}
+
+impl fmt::Display for Pair<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.0, self.1)
+    }
+}
+
+#[test]
+fn test_lifetime_implementor() {
+    let s = String::from("abc");
+    let view = StrView(&s);
+
+    assert_eq!(
+        Ok("abc".to_owned()),
+        dyn_dyn_cast!(Base => fmt::Display, &view as &dyn Base)
+            .map(|d| d.to_string())
+            .map_err(|_| ())
+    );
+
+    let t = String::from("def");
+    let pair = Pair(&s, &t);
+
+    assert_eq!(
+        Ok("abcdef".to_owned()),
+        dyn_dyn_cast!(Base => fmt::Display, &pair as &dyn Base)
+            .map(|d| d.to_string())
+            .map_err(|_| ())
+    );
+}
+
+#[test]
+fn test_lifetime_implementor_table() {
+    let s = String::from("abc");
+    let view = &StrView(&s) as &dyn Base;
+    let table = <&dyn Base as GetDynDynTable<dyn Base>>::get_dyn_dyn_table(&view);
+
+    assert_eq!(TypeId::of::<StrView<'static>>(), table.type_id());
+    assert!(!table.is::<StrView<'static>>());
+    assert!(dyn_dyn_downcast!(Base => StrView<'static>, view).is_err());
+}
+
+#[dyn_dyn_impl(fmt::Display)]
+impl Base for &str {}
+
+#[test]
+fn test_lifetime_elided_implementor() {
+    let s = String::from("abc");
+    let r: &str = &s;
+    let table = <&dyn Base as GetDynDynTable<dyn Base>>::get_dyn_dyn_table(&(&r as &dyn Base));
+
+    assert_eq!(TypeId::of::<&'static str>(), table.type_id());
+    assert_eq!(
+        Ok("abc".to_owned()),
+        dyn_dyn_cast!(Base => fmt::Display, &r as &dyn Base)
+            .map(|d| d.to_string())
+            .map_err(|_| ())
+    );
+}
+
+#[test]
+fn test_lifetime_elided_implementor_downcast() {
+    let s = String::from("abc");
+    let r: &str = &s;
+    let base = &r as &dyn Base;
+
+    assert!(
+        !<&dyn Base as GetDynDynTable<dyn Base>>::get_dyn_dyn_table(&base).is::<&'static str>()
+    );
+    assert!(dyn_dyn_downcast!(Base => &'static str, base).is_err());
+}
+
+#[dyn_dyn_base]
+trait Deserializer<'de> {}
+
+trait Visitor<'de> {
+    fn visit(&mut self, s: &'de str);
+    fn get(&self) -> Option<&'de str>;
+}
+
+trait StaticVisitor<'de> {}
+
+#[derive(Default)]
+struct StrVisitor<'de>(Option<&'de str>);
+
+#[dyn_dyn_impl(Visitor<'de>, StaticVisitor<'static>)]
+impl<'de> Deserializer<'de> for StrVisitor<'de> {}
+
+impl<'de> Visitor<'de> for StrVisitor<'de> {
+    fn visit(&mut self, s: &'de str) {
+        self.0 = Some(s);
+    }
+
+    fn get(&self) -> Option<&'de str> {
+        self.0
+    }
+}
+
+impl<'de> StaticVisitor<'static> for StrVisitor<'de> {}
+
+struct CountVisitor;
+
+#[dyn_dyn_impl(Visitor<'de>)]
+impl<'de> Deserializer<'de> for CountVisitor {}
+
+impl<'de> Visitor<'de> for CountVisitor {
+    fn visit(&mut self, _: &'de str) {}
+
+    fn get(&self) -> Option<&'de str> {
+        None
+    }
+}
+
+fn visit<'de>(d: &mut dyn Deserializer<'de>, s: &'de str) -> Option<&'de str> {
+    let v: &mut dyn Visitor<'de> = dyn_dyn_cast!(mut Deserializer<'de> => Visitor<'de>, d).ok()?;
+
+    v.visit(s);
+    v.get()
+}
+
+#[test]
+fn test_lifetime_exposed_trait() {
+    let s = String::from("abc");
+    let mut v = StrVisitor::default();
+
+    assert_eq!(Some("abc"), visit(&mut v, &s));
+    assert_eq!(Some("abc"), v.0);
+    assert_eq!(None, visit(&mut CountVisitor, &s));
+
+    assert!(
+        dyn_dyn_cast!(Deserializer<'_> => StaticVisitor<'static>, &v as &dyn Deserializer).is_ok()
+    );
+    assert!(dyn_dyn_cast!(Deserializer<'_> => StaticVisitor<'static>, &CountVisitor as &dyn Deserializer).is_err());
+}
+
+#[test]
+fn test_lifetime_exposed_trait_chained() {
+    #[dyn_dyn_base]
+    trait Outer<'de> {}
+
+    #[dyn_dyn_impl(Deserializer<'de>)]
+    impl<'de> Outer<'de> for StrVisitor<'de> {}
+
+    fn visit_outer<'de>(o: &mut dyn Outer<'de>, s: &'de str) -> Option<&'de str> {
+        let v = dyn_dyn_cast!(mut Outer<'de> => Deserializer<'de> => Visitor<'de>, o).ok()?;
+
+        v.visit(s);
+        v.get()
+    }
+
+    let s = String::from("abc");
+
+    assert_eq!(Some("abc"), visit_outer(&mut StrVisitor::default(), &s));
+}
+
+trait Matches<'x> {
+    fn matches(&self, s: &'x str) -> bool;
+}
+
+struct Prefix(&'static str);
+
+#[dyn_dyn_impl(for<'x> Matches<'x>)]
+impl Base for Prefix {}
+
+impl<'x> Matches<'x> for Prefix {
+    fn matches(&self, s: &'x str) -> bool {
+        s.starts_with(self.0)
+    }
+}
+
+#[test]
+fn test_lifetime_higher_ranked_exposed_trait() {
+    let prefix = &Prefix("ab") as &dyn Base;
+    let s = String::from("abc");
+
+    let m: &dyn for<'x> Matches<'x> = dyn_dyn_cast!(Base => for<'x> Matches<'x>, prefix)
+        .ok()
+        .unwrap();
+    assert!(m.matches(&s));
+    assert!(!m.matches("def"));
+
+    let table = <&dyn Base as GetDynDynTable<dyn Base>>::get_dyn_dyn_table(&prefix);
+    assert!(table.can_cast::<dyn for<'x> Matches<'x>>());
+}
+
+struct Words(std::vec::IntoIter<&'static str>);
+
+#[dyn_dyn_impl(Iterator<Item = &str>)]
+impl Base for Words {}
+
+impl Iterator for Words {
+    type Item = &'static str;
+
+    fn next(&mut self) -> Option<&'static str> {
+        self.0.next()
+    }
+}
+
+#[test]
+fn test_lifetime_elided_exposed_trait() {
+    let mut words = Words(vec!["a", "b"].into_iter());
+    let words = &mut words as &mut dyn Base;
+
+    let it: &mut dyn Iterator<Item = &'static str> =
+        dyn_dyn_cast!(mut Base => Iterator<Item = &'static str>, words)
+            .ok()
+            .unwrap();
+    assert_eq!(Some("a"), it.next());
+
+    let table = <&mut dyn Base as GetDynDynTable<dyn Base>>::get_dyn_dyn_table(&words);
+    assert!(table.can_cast::<dyn Iterator<Item = &'static str>>());
+}
+
+struct Pred<'a>(fn(&str) -> bool, PhantomData<&'a str>);
+
+#[dyn_dyn_impl(AsRef<fn(&str) -> bool>)]
+impl<'a> Base for Pred<'a> {}
+
+impl<'a> AsRef<fn(&str) -> bool> for Pred<'a> {
+    fn as_ref(&self) -> &fn(&str) -> bool {
+        &self.0
+    }
+}
+
+#[test]
+fn test_lifetime_elided_fn_pointer_exposed_trait() {
+    let s = String::from("abc");
+    let pred = Pred(|s| s.is_empty(), PhantomData);
+    let pred = &pred as &dyn Base;
+
+    let f = dyn_dyn_cast!(Base => AsRef<fn(&str) -> bool>, pred)
+        .ok()
+        .unwrap();
+    assert!((f.as_ref())(""));
+    assert!(!(f.as_ref())(&s));
+}
+
+// The impls for fn(&'static u8) and fn(&u8) overlap as far as the coherence leak check is concerned, although they are accepted.
+#[allow(coherence_leak_check)]
+mod fn_pointer {
+    use super::Base;
+    use dyn_dyn::dyn_dyn_impl;
+
+    pub trait FnKind {
+        fn kind(&self) -> &'static str;
+    }
+
+    #[dyn_dyn_impl(FnKind)]
+    impl Base for fn(&'static u8) {}
+
+    impl FnKind for fn(&'static u8) {
+        fn kind(&self) -> &'static str {
+            "static"
+        }
+    }
+
+    // The elided lifetime in this self type is higher-ranked, so it must not be filled in with 'static, which would build the table of this
+    // impl from the vtables of the one above.
+    #[dyn_dyn_impl(FnKind)]
+    impl Base for fn(&u8) {}
+
+    impl FnKind for for<'a> fn(&'a u8) {
+        fn kind(&self) -> &'static str {
+            "higher-ranked"
+        }
+    }
+}
+
+#[test]
+fn test_lifetime_elided_fn_pointer_implementor() {
+    use fn_pointer::FnKind;
+
+    fn ignore(_: &u8) {}
+
+    let higher_ranked: fn(&u8) = ignore;
+    let fixed: fn(&'static u8) = ignore;
+    let higher_ranked = &higher_ranked as &dyn Base;
+    let fixed = &fixed as &dyn Base;
+
+    assert_eq!(
+        Ok("higher-ranked"),
+        dyn_dyn_cast!(Base => FnKind, higher_ranked)
+            .map(|f| f.kind())
+            .map_err(|_| ())
+    );
+    assert_eq!(
+        Ok("static"),
+        dyn_dyn_cast!(Base => FnKind, fixed)
+            .map(|f| f.kind())
+            .map_err(|_| ())
+    );
+    assert!(
+        <&dyn Base as GetDynDynTable<dyn Base>>::get_dyn_dyn_table(&higher_ranked).is::<fn(&u8)>()
+    );
+    assert!(dyn_dyn_downcast!(Base => fn(&u8), higher_ranked).is_ok());
+}
@@ -0,0 +1,123 @@
+use dyn_dyn::{dyn_dyn_base, dyn_dyn_impl, dyn_dyn_match};
+
+#[dyn_dyn_base]
+trait Shape {}
+
+trait Area {
+    fn area(&self) -> u32;
+}
+
+trait Scale {
+    fn scale(&mut self, factor: u32);
+}
+
+trait Name {
+    fn name(&self) -> &'static str;
+}
+
+struct Square(u32);
+struct Point;
+
+#[dyn_dyn_impl(Area, Scale, Name)]
+impl Shape for Square {}
+
+#[dyn_dyn_impl(Name)]
+impl Shape for Point {}
+
+impl Area for Square {
+    fn area(&self) -> u32 {
+        self.0 * self.0
+    }
+}
+
+impl Scale for Square {
+    fn scale(&mut self, factor: u32) {
+        self.0 *= factor;
+    }
+}
+
+impl Name for Square {
+    fn name(&self) -> &'static str {
+        "square"
+    }
+}
+
+impl Name for Point {
+    fn name(&self) -> &'static str {
+        "point"
+    }
+}
+
+fn describe(s: &dyn Shape) -> String {
+    dyn_dyn_match!(Shape, s {
+        Area(a) => format!("area {}", a.area()),
+        Name(n) => n.name().to_owned(),
+        _ => "unknown".to_owned(),
+    })
+}
+
+#[test]
+fn test_match_ref() {
+    assert_eq!("area 4", describe(&Square(2)));
+    assert_eq!("point", describe(&Point));
+}
+
+#[test]
+fn test_match_mut() {
+    let mut s = Square(2);
+
+    let scaled = dyn_dyn_match!(mut Shape, &mut s as &mut dyn Shape {
+        Scale(s) => {
+            s.scale(3);
+            true
+        }
+        _ => false,
+    });
+
+    assert!(scaled);
+    assert_eq!(6, s.0);
+}
+
+#[test]
+fn test_match_fallthrough() {
+    let p = Point;
+    let r: &dyn Shape = &p;
+
+    let fallthrough = dyn_dyn_match!(Shape, r {
+        Area(_) => None,
+        other => Some(other),
+    });
+
+    assert!(core::ptr::eq(r, fallthrough.unwrap()));
+}
+
+#[test]
+fn test_match_early_return() {
+    fn area(s: &dyn Shape) -> Option<u32> {
+        let a = dyn_dyn_match!(Shape, s {
+            Area(a) => a,
+            _ => return None,
+        });
+
+        Some(a.area())
+    }
+
+    assert_eq!(Some(9), area(&Square(3)));
+    assert_eq!(None, area(&Point));
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_match_move() {
+    let s: Box<dyn Shape> = Box::new(Square(4));
+
+    let area = dyn_dyn_match!(move Shape, s {
+        Area(a) => {
+            let a: Box<dyn Area> = a;
+            a.area()
+        }
+        _ => 0,
+    });
+
+    assert_eq!(16, area);
+}
@@ -0,0 +1,162 @@
+use dyn_dyn::{dyn_dyn_base, dyn_dyn_impl};
+
+#[dyn_dyn_base]
+trait Base {}
+
+trait TestTrait {
+    fn test(&self) -> u32;
+    fn set(&mut self, val: u32);
+}
+
+trait OtherTrait {}
+
+struct TestStruct(u32);
+
+#[dyn_dyn_impl(TestTrait)]
+impl Base for TestStruct {}
+
+impl TestTrait for TestStruct {
+    fn test(&self) -> u32 {
+        self.0
+    }
+
+    fn set(&mut self, val: u32) {
+        self.0 = val;
+    }
+}
+
+#[test]
+fn test_cast_ref() {
+    let b: &dyn Base = &TestStruct(1234);
+
+    assert_eq!(
+        Some(1234),
+        b.dyn_dyn_cast_ref::<dyn TestTrait>().map(|t| t.test())
+    );
+    assert!(b.dyn_dyn_cast_ref::<dyn OtherTrait>().is_none());
+}
+
+#[test]
+fn test_cast_mut() {
+    let mut s = TestStruct(0);
+    let b: &mut dyn Base = &mut s;
+
+    b.dyn_dyn_cast_mut::<dyn TestTrait>().unwrap().set(1234);
+    assert!(b.dyn_dyn_cast_mut::<dyn OtherTrait>().is_none());
+    assert_eq!(1234, s.0);
+}
+
+#[test]
+fn test_implements() {
+    let b: &dyn Base = &TestStruct(0);
+
+    assert!(b.dyn_dyn_implements::<dyn TestTrait>());
+    assert!(!b.dyn_dyn_implements::<dyn OtherTrait>());
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_cast_owned() {
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    let b: Box<dyn Base> = Box::new(TestStruct(1234));
+    let b = b
+        .dyn_dyn_cast_box::<dyn OtherTrait>()
+        .map(|_| ())
+        .unwrap_err();
+    assert_eq!(
+        1234,
+        b.dyn_dyn_cast_box::<dyn TestTrait>().ok().unwrap().test()
+    );
+
+    let r: Rc<dyn Base> = Rc::new(TestStruct(1234));
+    let r = r
+        .dyn_dyn_cast_rc::<dyn OtherTrait>()
+        .map(|_| ())
+        .unwrap_err();
+    assert_eq!(
+        1234,
+        r.dyn_dyn_cast_rc::<dyn TestTrait>().ok().unwrap().test()
+    );
+
+    let a: Arc<dyn Base> = Arc::new(TestStruct(1234));
+    let a = a
+        .dyn_dyn_cast_arc::<dyn OtherTrait>()
+        .map(|_| ())
+        .unwrap_err();
+    assert_eq!(
+        1234,
+        a.dyn_dyn_cast_arc::<dyn TestTrait>().ok().unwrap().test()
+    );
+}
+
+#[test]
+fn test_cast_lifetime_implementor() {
+    trait StrTrait {}
+
+    #[allow(dead_code)]
+    struct StrView<'a>(&'a str);
+
+    #[dyn_dyn_impl(StrTrait)]
+    impl<'a> Base for StrView<'a> {}
+    impl<'a> StrTrait for StrView<'a> {}
+
+    // The generated methods are only available on a 'static base trait object, so the concrete type is known to be 'static here even
+    // though its table has its lifetimes erased. Borrowing a local string instead would fail to compile.
+    let b: &(dyn Base + 'static) = &StrView("test");
+
+    assert!(b.dyn_dyn_implements::<dyn StrTrait>());
+    assert!(b.dyn_dyn_cast_ref::<dyn StrTrait>().is_some());
+}
+
+#[test]
+fn test_cast_generic_base() {
+    #[dyn_dyn_base]
+    trait GenericBase<D: 'static> {}
+
+    struct GenericStruct;
+
+    #[dyn_dyn_impl(TestTrait)]
+    impl GenericBase<u32> for GenericStruct {}
+    impl TestTrait for GenericStruct {
+        fn test(&self) -> u32 {
+            1234
+        }
+
+        fn set(&mut self, _: u32) {}
+    }
+
+    let b: &dyn GenericBase<u32> = &GenericStruct;
+
+    assert_eq!(
+        Some(1234),
+        b.dyn_dyn_cast_ref::<dyn TestTrait>().map(|t| t.test())
+    );
+}
+
+#[test]
+fn test_trait_methods_not_shadowed() {
+    #[dyn_dyn_base]
+    trait Capabilities {
+        fn implements(&self, name: &str) -> bool;
+        fn cast_ref(&self) -> u32;
+    }
+
+    #[dyn_dyn_impl(TestTrait)]
+    impl Capabilities for TestStruct {
+        fn implements(&self, name: &str) -> bool {
+            name == "test"
+        }
+
+        fn cast_ref(&self) -> u32 {
+            self.0
+        }
+    }
+
+    let b: &dyn Capabilities = &TestStruct(1234);
+
+    assert!(b.implements("test"));
+    assert_eq!(1234, b.cast_ref());
+    assert!(b.dyn_dyn_implements::<dyn TestTrait>());
+}
@@ -0,0 +1,195 @@
+use dyn_dyn::{dyn_dyn_base, dyn_dyn_cast, dyn_dyn_impl};
+
+#[dyn_dyn_base]
+trait Widget {
+    fn id(&self) -> u32;
+}
+
+trait Render {
+    fn render(&self) -> String;
+}
+
+trait Layout {
+    fn width(&self) -> u32;
+}
+
+trait Focus {}
+
+struct Label(u32);
+
+#[dyn_dyn_impl(Render, Layout)]
+impl Widget for Label {
+    fn id(&self) -> u32 {
+        self.0
+    }
+}
+
+impl Render for Label {
+    fn render(&self) -> String {
+        format!("label {}", self.0)
+    }
+}
+
+impl Layout for Label {
+    fn width(&self) -> u32 {
+        self.0 * 2
+    }
+}
+
+#[test]
+fn test_multi_ref() {
+    let w: &dyn Widget = &Label(5);
+
+    let (render, layout) = dyn_dyn_cast!(Widget => (Render, Layout), w).ok().unwrap();
+    assert_eq!("label 5", render.render());
+    assert_eq!(10, layout.width());
+
+    assert_eq!(
+        5,
+        dyn_dyn_cast!(Widget => (Render, Focus), w)
+            .map(|_| ())
+            .unwrap_err()
+            .id()
+    );
+}
+
+#[test]
+fn test_multi_single() {
+    let w: &dyn Widget = &Label(5);
+
+    let (render,) = dyn_dyn_cast!(Widget => (Render,), w).ok().unwrap();
+    assert_eq!("label 5", render.render());
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_multi_ref_box() {
+    let w: Box<dyn Widget> = Box::new(Label(5));
+
+    let (layout, render) = dyn_dyn_cast!(Widget => (Layout, Render), &w).ok().unwrap();
+    assert_eq!("label 5", render.render());
+    assert_eq!(10, layout.width());
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_multi_move() {
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    let w: Rc<dyn Widget> = Rc::new(Label(5));
+
+    let (render, layout) = dyn_dyn_cast!(move Widget => (Render, Layout), w.clone())
+        .ok()
+        .unwrap();
+    assert_eq!(3, Rc::strong_count(&w));
+    assert_eq!("label 5", render.render());
+    assert_eq!(10, layout.width());
+
+    drop((render, layout));
+
+    let w = dyn_dyn_cast!(move Widget => (Render, Focus), w)
+        .map(|_| ())
+        .unwrap_err();
+    assert_eq!(1, Rc::strong_count(&w));
+
+    let w: Arc<dyn Widget> = Arc::new(Label(5));
+
+    let (render, layout) = dyn_dyn_cast!(move Widget => (Render, Layout), w)
+        .ok()
+        .unwrap();
+    assert_eq!("label 5", render.render());
+    assert_eq!(10, layout.width());
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_multi_error() {
+    use std::any::TypeId;
+
+    let w: &dyn Widget = &Label(5);
+
+    let err = dyn_dyn_cast!(try Widget => (Render, Focus, Layout), w)
+        .map(|_| ())
+        .unwrap_err();
+    assert_eq!(TypeId::of::<dyn Focus>(), err.target_type_id());
+    assert_eq!(5, err.into_inner().id());
+}
+
+#[test]
+fn test_multi_chain() {
+    #[dyn_dyn_base]
+    trait Base {}
+
+    struct Outer;
+
+    #[dyn_dyn_impl(Widget)]
+    impl Base for Outer {}
+
+    #[dyn_dyn_impl(Render, Layout)]
+    impl Widget for Outer {
+        fn id(&self) -> u32 {
+            7
+        }
+    }
+
+    impl Render for Outer {
+        fn render(&self) -> String {
+            "outer".to_owned()
+        }
+    }
+
+    impl Layout for Outer {
+        fn width(&self) -> u32 {
+            1
+        }
+    }
+
+    let b: &dyn Base = &Outer;
+
+    let (render, layout) = dyn_dyn_cast!(Base => Widget => (Render, Layout), b)
+        .ok()
+        .unwrap();
+    assert_eq!("outer", render.render());
+    assert_eq!(1, layout.width());
+    assert!(dyn_dyn_cast!(Base => Widget => (Render, Focus), b).is_err());
+}
+
+#[test]
+fn test_multi_lifetimes() {
+    #[dyn_dyn_base]
+    trait Deserializer<'de> {}
+    trait Visitor<'de> {
+        fn visit(&self) -> &'de str;
+    }
+
+    struct StrDeserializer<'de>(&'de str);
+
+    #[dyn_dyn_impl(Visitor<'de>, Render)]
+    impl<'de> Deserializer<'de> for StrDeserializer<'de> {}
+
+    impl<'de> Visitor<'de> for StrDeserializer<'de> {
+        fn visit(&self) -> &'de str {
+            self.0
+        }
+    }
+
+    impl<'de> Render for StrDeserializer<'de> {
+        fn render(&self) -> String {
+            self.0.to_owned()
+        }
+    }
+
+    fn views<'a, 'de>(
+        d: &'a dyn Deserializer<'de>,
+    ) -> Option<(&'a dyn Visitor<'de>, &'a dyn Render)> {
+        dyn_dyn_cast!(Deserializer<'de> => (Visitor<'de>, Render), d).ok()
+    }
+
+    let s = String::from("test");
+    let d = StrDeserializer(&s);
+    let (visitor, render) = views(&d).unwrap();
+
+    assert_eq!("test", visitor.visit());
+    assert_eq!("test", render.render());
+}
@@ -0,0 +1,109 @@
+use core::marker::PhantomPinned;
+use core::pin::Pin;
+use dyn_dyn::{dyn_dyn_base, dyn_dyn_cast, dyn_dyn_impl};
+
+#[dyn_dyn_base]
+trait Task {}
+
+trait Poll {
+    fn poll(self: Pin<&mut Self>) -> u32;
+    fn addr(&self) -> usize;
+}
+
+struct Counter(u32, PhantomPinned);
+
+#[dyn_dyn_impl(Poll)]
+impl Task for Counter {}
+
+impl Poll for Counter {
+    fn poll(self: Pin<&mut Self>) -> u32 {
+        // SAFETY: The counter is not moved out of the pinned reference.
+        let this = unsafe { self.get_unchecked_mut() };
+        this.0 += 1;
+        this.0
+    }
+
+    fn addr(&self) -> usize {
+        self as *const Self as usize
+    }
+}
+
+struct Idle;
+
+#[dyn_dyn_impl]
+impl Task for Idle {}
+
+fn counter() -> Counter {
+    Counter(0, PhantomPinned)
+}
+
+#[test]
+fn test_pin_move_ref_mut() {
+    let mut c = counter();
+    // SAFETY: The counter is never moved after being pinned here.
+    let p: Pin<&mut dyn Task> = unsafe { Pin::new_unchecked(&mut c) };
+
+    let mut p: Pin<&mut dyn Poll> = dyn_dyn_cast!(move Task => Poll, p).ok().unwrap();
+    assert_eq!(1, p.as_mut().poll());
+    assert_eq!(2, p.poll());
+}
+
+#[test]
+fn test_pin_mut() {
+    let mut p: Pin<Box<dyn Task>> = Box::pin(counter());
+
+    let poll: Pin<&mut dyn Poll> = dyn_dyn_cast!(mut Task => Poll, &mut p).ok().unwrap();
+    assert_eq!(1, poll.poll());
+
+    let poll: Pin<&mut dyn Poll> = dyn_dyn_cast!(mut Task => Poll, &mut p).ok().unwrap();
+    assert_eq!(2, poll.poll());
+}
+
+#[test]
+fn test_pin_ref() {
+    let p: Pin<Box<dyn Task>> = Box::pin(counter());
+    let addr = &*p as *const dyn Task as *const () as usize;
+
+    let poll: &dyn Poll = dyn_dyn_cast!(Task => Poll, &p).ok().unwrap();
+    assert_eq!(addr, poll.addr());
+
+    let poll: Pin<&dyn Poll> = dyn_dyn_cast!(move Task => Poll, p.as_ref()).ok().unwrap();
+    assert_eq!(addr, poll.addr());
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_pin_box() {
+    let p: Pin<Box<dyn Task>> = Box::pin(counter());
+
+    let mut poll: Pin<Box<dyn Poll>> = dyn_dyn_cast!(move Task => Poll, p).ok().unwrap();
+    assert_eq!(1, poll.as_mut().poll());
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_pin_rc_arc() {
+    let rc: Pin<std::rc::Rc<dyn Task>> = std::rc::Rc::pin(counter());
+    assert!(dyn_dyn_cast!(move Task => Poll, rc).is_ok());
+
+    let arc: Pin<std::sync::Arc<dyn Task>> = std::sync::Arc::pin(counter());
+    assert!(dyn_dyn_cast!(move Task => Poll, arc).is_ok());
+}
+
+#[test]
+fn test_pin_fail() {
+    let idle = Idle;
+    let p: Pin<&dyn Task> = Pin::<&Idle>::new(&idle);
+
+    let p: Pin<&dyn Task> = dyn_dyn_cast!(move Task => Poll, p).err().unwrap();
+    assert!(dyn_dyn_cast!(Task => Poll, &p).is_err());
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_pin_box_fail() {
+    let p: Pin<Box<dyn Task>> = Box::pin(Idle);
+
+    let p: Pin<Box<dyn Task>> = dyn_dyn_cast!(move Task => Poll, p).err().unwrap();
+    assert!(dyn_dyn_cast!(Task => Poll, &p).is_err());
+}
@@ -0,0 +1,187 @@
+#![cfg(feature = "alloc")]
+
+use core::marker::PhantomData;
+use core::ops::{Deref, DerefMut};
+use dyn_dyn::{dyn_dyn_base, dyn_dyn_cast, dyn_dyn_impl, DynDyn, DynDynPointer};
+use stable_deref_trait::StableDeref;
+use std::rc::Rc;
+
+#[dyn_dyn_base]
+trait Base {}
+
+trait Trait {
+    fn get(&self) -> u32;
+    fn set(&mut self, val: u32);
+}
+
+struct StructA(u32);
+struct StructB;
+
+#[dyn_dyn_impl(Trait)]
+impl Base for StructA {}
+
+#[dyn_dyn_impl]
+impl Base for StructB {}
+
+impl Trait for StructA {
+    fn get(&self) -> u32 {
+        self.0
+    }
+
+    fn set(&mut self, val: u32) {
+        self.0 = val;
+    }
+}
+
+#[derive(DynDyn)]
+struct Obj<T: ?Sized, Tag = ()>(Rc<T>, PhantomData<Tag>)
+where
+    Tag: 'static;
+
+impl<T: ?Sized, Tag: 'static> Obj<T, Tag> {
+    fn new(val: Rc<T>) -> Self {
+        Obj(val, PhantomData)
+    }
+}
+
+impl<T: ?Sized, Tag: 'static> Deref for Obj<T, Tag> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+// SAFETY: Obj is a simple wrapper around Rc.
+unsafe impl<T: ?Sized, Tag: 'static> StableDeref for Obj<T, Tag> {}
+
+impl<T: ?Sized, Tag: 'static> DynDynPointer for Obj<T, Tag> {
+    type Rebind<D: ?Sized> = Obj<D, Tag>;
+
+    fn into_raw(this: Self) -> *const T {
+        Rc::into_raw(this.0)
+    }
+
+    unsafe fn from_raw<D: ?Sized>(ptr: *const D) -> Obj<D, Tag> {
+        // SAFETY: The pointer was returned by Rc::into_raw and still refers to the same object.
+        Obj::new(unsafe { Rc::from_raw(ptr) })
+    }
+}
+
+#[derive(DynDyn)]
+struct Unique<T: ?Sized>(Box<T>);
+
+impl<T: ?Sized> Deref for Unique<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: ?Sized> DerefMut for Unique<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+// SAFETY: Unique is a simple wrapper around Box.
+unsafe impl<T: ?Sized> StableDeref for Unique<T> {}
+
+impl<T: ?Sized> DynDynPointer for Unique<T> {
+    type Rebind<D: ?Sized> = Unique<D>;
+
+    fn into_raw(this: Self) -> *const T {
+        Box::into_raw(this.0)
+    }
+
+    unsafe fn from_raw<D: ?Sized>(ptr: *const D) -> Unique<D> {
+        // SAFETY: The pointer was returned by Box::into_raw and still refers to the same object.
+        Unique(unsafe { Box::from_raw(ptr as *mut D) })
+    }
+}
+
+// Dereferences to the first object but hands out the second one from into_raw, which the cast must not trust.
+#[derive(DynDyn)]
+struct Mismatched<T: ?Sized>(Box<T>, Box<T>);
+
+impl<T: ?Sized> Deref for Mismatched<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+// SAFETY: Mismatched always dereferences to the contents of its first Box.
+unsafe impl<T: ?Sized> StableDeref for Mismatched<T> {}
+
+impl<T: ?Sized> DynDynPointer for Mismatched<T> {
+    type Rebind<D: ?Sized> = Box<D>;
+
+    fn into_raw(this: Self) -> *const T {
+        Box::into_raw(this.1)
+    }
+
+    unsafe fn from_raw<D: ?Sized>(ptr: *const D) -> Box<D> {
+        // SAFETY: The pointer was returned by Box::into_raw and still refers to the same object.
+        unsafe { Box::from_raw(ptr as *mut D) }
+    }
+}
+
+#[test]
+fn test_pointer_move() {
+    let rc: Rc<dyn Base> = Rc::new(StructA(1));
+    let obj: Obj<dyn Base> = Obj::new(rc.clone());
+
+    let obj: Obj<dyn Trait> = dyn_dyn_cast!(move Base => Trait, obj).ok().unwrap();
+    assert_eq!(1, obj.get());
+    assert_eq!(2, Rc::strong_count(&rc));
+
+    drop(obj);
+    assert_eq!(1, Rc::strong_count(&rc));
+}
+
+#[test]
+fn test_pointer_move_fail() {
+    let obj: Obj<dyn Base> = Obj::new(Rc::new(StructB));
+
+    let obj: Obj<dyn Base> = dyn_dyn_cast!(move Base => Trait, obj).err().unwrap();
+    assert_eq!(1, Rc::strong_count(&obj.0));
+}
+
+#[test]
+fn test_pointer_ref() {
+    let obj: Obj<dyn Base> = Obj::new(Rc::new(StructA(2)));
+
+    let r: &dyn Trait = dyn_dyn_cast!(Base => Trait, &obj).ok().unwrap();
+    assert_eq!(2, r.get());
+}
+
+#[test]
+fn test_pointer_mut() {
+    let mut unique: Unique<dyn Base> = Unique(Box::new(StructA(3)));
+
+    dyn_dyn_cast!(mut Base => Trait, &mut unique)
+        .ok()
+        .unwrap()
+        .set(4);
+
+    let unique: Unique<dyn Trait> = dyn_dyn_cast!(move Base => Trait, unique).ok().unwrap();
+    assert_eq!(4, unique.get());
+}
+
+#[test]
+fn test_pointer_cast_fn() {
+    let obj: Obj<dyn Base> = Obj::new(Rc::new(StructA(5)));
+
+    assert!(dyn_dyn::cast::<dyn Base, dyn Trait, _>(obj).is_ok());
+}
+
+#[test]
+#[should_panic(expected = "DynDynPointer::into_raw returned a pointer to a different object")]
+fn test_pointer_mismatched_into_raw() {
+    let obj: Mismatched<dyn Base> = Mismatched(Box::new(StructA(6)), Box::new(StructB));
+
+    let _ = dyn_dyn_cast!(move Base => Trait, obj);
+}
@@ -0,0 +1,102 @@
+use core::ptr::NonNull;
+use dyn_dyn::{dyn_dyn_base, dyn_dyn_impl, DynDynBaseObject, DynDynRawPtr};
+
+#[dyn_dyn_base]
+trait Node {}
+
+trait Value {
+    fn value(&self) -> u32;
+}
+
+trait Counter {
+    fn increment(&mut self);
+}
+
+struct Leaf(u32);
+struct Empty;
+
+#[dyn_dyn_impl(Value, Counter)]
+impl Node for Leaf {}
+
+#[dyn_dyn_impl]
+impl Node for Empty {}
+
+impl Value for Leaf {
+    fn value(&self) -> u32 {
+        self.0
+    }
+}
+
+impl Counter for Leaf {
+    fn increment(&mut self) {
+        self.0 += 1;
+    }
+}
+
+#[test]
+fn test_cast_raw_const() {
+    let leaf = Leaf(3);
+    let ptr: *const dyn Node = &leaf;
+
+    // SAFETY: ptr points to leaf, which is live.
+    let value = unsafe { dyn_dyn::cast_raw::<dyn Node, dyn Value, _>(ptr) }.unwrap();
+
+    assert_eq!(ptr as *const (), value as *const ());
+    // SAFETY: value points to leaf, which is live.
+    assert_eq!(3, unsafe { &*value }.value());
+}
+
+#[test]
+fn test_cast_raw_mut() {
+    let mut leaf = Leaf(3);
+    let ptr: *mut dyn Node = &mut leaf;
+
+    // SAFETY: ptr points to leaf, which is live.
+    let counter = unsafe { dyn_dyn::cast_raw::<dyn Node, dyn Counter, _>(ptr) }.unwrap();
+
+    // SAFETY: counter points to leaf, which is live and not otherwise borrowed.
+    unsafe { (*counter).increment() };
+    assert_eq!(4, leaf.0);
+}
+
+#[test]
+fn test_cast_raw_non_null() {
+    let leaf = Leaf(5);
+    let ptr: NonNull<dyn Node> = NonNull::from(&leaf);
+
+    // SAFETY: ptr points to leaf, which is live.
+    let value = unsafe { dyn_dyn::cast_raw::<dyn Node, dyn Value, _>(ptr) }.unwrap();
+
+    // SAFETY: value points to leaf, which is live.
+    assert_eq!(5, unsafe { value.as_ref() }.value());
+}
+
+#[test]
+fn test_cast_raw_fail() {
+    let empty = Empty;
+    let ptr: *const dyn Node = &empty;
+
+    // SAFETY: ptr points to empty, which is live.
+    let err = unsafe { dyn_dyn::cast_raw::<dyn Node, dyn Value, _>(ptr) }.unwrap_err();
+
+    assert!(core::ptr::eq(ptr, err));
+}
+
+#[test]
+fn test_cast_raw_generic() {
+    // SAFETY: Every pointer in ptrs must point to a live object.
+    unsafe fn count_values<B: ?Sized + DynDynBaseObject + 'static, P: DynDynRawPtr<B>>(
+        ptrs: &[P],
+    ) -> usize {
+        ptrs.iter()
+            // SAFETY: Guaranteed by the caller.
+            .filter(|&&p| unsafe { dyn_dyn::cast_raw::<B, dyn Value, _>(p) }.is_ok())
+            .count()
+    }
+
+    let (a, b, c) = (Leaf(1), Empty, Leaf(2));
+    let ptrs: [*const dyn Node; 3] = [&a, &b, &c];
+
+    // SAFETY: All of the pointers point to live objects.
+    assert_eq!(2, unsafe { count_values::<dyn Node, _>(&ptrs) });
+}
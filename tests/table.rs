@@ -0,0 +1,125 @@
+use core::alloc::Layout;
+use core::any::TypeId;
+use dyn_dyn::{dyn_dyn_base, dyn_dyn_impl, DynDynTable, GetDynDynTable};
+
+#[dyn_dyn_base]
+trait Base {}
+
+struct StructA(#[allow(dead_code)] u64);
+
+#[dyn_dyn_impl]
+impl Base for StructA {}
+
+struct StructB;
+
+#[dyn_dyn_impl]
+impl Base for StructB {}
+
+fn table_of(r: &dyn Base) -> DynDynTable {
+    <&dyn Base as GetDynDynTable<dyn Base>>::get_dyn_dyn_table(&r)
+}
+
+#[test]
+fn test_concrete_type_id() {
+    assert_eq!(TypeId::of::<StructA>(), table_of(&StructA(0)).type_id());
+    assert_eq!(TypeId::of::<StructB>(), table_of(&StructB).type_id());
+
+    assert!(table_of(&StructA(0)).is::<StructA>());
+    assert!(!table_of(&StructA(0)).is::<StructB>());
+}
+
+#[test]
+fn test_concrete_layout() {
+    assert_eq!(Layout::new::<StructA>(), table_of(&StructA(0)).layout());
+    assert_eq!(Layout::new::<StructB>(), table_of(&StructB).layout());
+}
+
+#[test]
+fn test_same_concrete_type() {
+    assert!(DynDynTable::same_concrete_type(
+        &table_of(&StructA(0)),
+        &table_of(&StructA(1))
+    ));
+    assert!(!DynDynTable::same_concrete_type(
+        &table_of(&StructA(0)),
+        &table_of(&StructB)
+    ));
+}
+
+#[cfg(feature = "dynamic-names")]
+#[test]
+fn test_concrete_type_name() {
+    assert_eq!(
+        core::any::type_name::<StructA>(),
+        table_of(&StructA(0)).type_name()
+    );
+}
+
+#[test]
+fn test_introspect() {
+    use core::fmt::Debug;
+
+    trait GenericTrait<T> {}
+    trait FnTrait<F: ?Sized> {}
+
+    #[derive(Debug)]
+    struct StructC(#[allow(dead_code)] u32);
+
+    #[dyn_dyn_impl(Debug, Debug + Send, GenericTrait<Vec<u8>>, FnTrait<dyn Fn(u8) -> u8>)]
+    impl Base for StructC {}
+    impl GenericTrait<Vec<u8>> for StructC {}
+    impl FnTrait<dyn Fn(u8) -> u8> for StructC {}
+
+    let table = dyn_dyn::introspect::<dyn Base>(&StructC(0));
+
+    assert_eq!(4, table.len());
+    assert_eq!(
+        vec![
+            "dyn Debug",
+            "dyn Debug + Send",
+            "dyn GenericTrait<Vec<u8>>",
+            "dyn FnTrait<dyn Fn(u8) -> u8>"
+        ],
+        table.into_iter().map(|e| e.name()).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        vec![
+            TypeId::of::<dyn Debug>(),
+            TypeId::of::<dyn Debug + Send>(),
+            TypeId::of::<dyn GenericTrait<Vec<u8>>>(),
+            TypeId::of::<dyn FnTrait<dyn Fn(u8) -> u8>>()
+        ],
+        table.into_iter().map(|e| e.type_id()).collect::<Vec<_>>()
+    );
+
+    for entry in table {
+        assert_eq!(core::mem::size_of::<StructC>(), entry.size_of());
+        assert_eq!(core::mem::align_of::<StructC>(), entry.align_of());
+    }
+
+    assert!(table.can_cast::<dyn Debug>());
+    assert!(table.can_cast::<dyn Debug + Send>());
+    assert!(!table.can_cast::<dyn Debug + Sync>());
+    assert!(!table_of(&StructA(0)).can_cast::<dyn Debug>());
+    assert!(table_of(&StructA(0)).is_empty());
+}
+
+#[test]
+fn test_introspect_lifetimes() {
+    #[dyn_dyn_base]
+    trait Deserializer<'de> {}
+    trait Visitor<'de> {}
+
+    struct StructC;
+
+    #[dyn_dyn_impl(Visitor<'de>)]
+    impl<'de> Deserializer<'de> for StructC {}
+    impl<'de> Visitor<'de> for StructC {}
+
+    let table = dyn_dyn::introspect::<dyn Deserializer<'_>>(&StructC);
+
+    assert_eq!(
+        vec!["dyn Visitor<'de>"],
+        table.into_iter().map(|e| e.name()).collect::<Vec<_>>()
+    );
+}
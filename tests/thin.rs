@@ -0,0 +1,174 @@
+#![cfg(feature = "alloc")]
+
+use core::cell::Cell;
+use core::fmt::Debug;
+use core::mem;
+use dyn_dyn::{
+    dyn_dyn_base, dyn_dyn_cast, dyn_dyn_impl, DynDynBase, DynDynTable, DynDynTableEntry,
+    DynDynThinArc, DynDynThinBox,
+};
+
+static EMPTY_TABLE: (u8, [DynDynTableEntry; 0]) = (0, []);
+
+trait CountingBase {
+    fn get_dyn_dyn_table(&self) -> DynDynTable;
+}
+
+// SAFETY: This is for testing purposes only, so we rely on internal implementation details that get around the safety requirements
+unsafe impl<'a> DynDynBase for dyn CountingBase + 'a {
+    fn get_dyn_dyn_table(&self) -> DynDynTable {
+        self.get_dyn_dyn_table()
+    }
+}
+
+struct CountingStruct<'a>(&'a Cell<usize>);
+
+impl<'a> CountingBase for CountingStruct<'a> {
+    fn get_dyn_dyn_table(&self) -> DynDynTable {
+        self.0.set(self.0.get() + 1);
+        DynDynTable::new_lifetime_erased::<CountingStruct<'static>>(&EMPTY_TABLE.1)
+    }
+}
+
+#[dyn_dyn_base]
+trait Base {
+    fn get(&self) -> u32;
+}
+
+trait Trait {
+    fn set(&mut self, val: u32);
+}
+
+#[derive(Debug)]
+struct TestStruct<'a>(u32, &'a Cell<usize>);
+
+#[dyn_dyn_impl(Trait, Debug)]
+impl<'a> Base for TestStruct<'a> {
+    fn get(&self) -> u32 {
+        self.0
+    }
+}
+
+impl<'a> Trait for TestStruct<'a> {
+    fn set(&mut self, val: u32) {
+        self.0 = val;
+    }
+}
+
+impl<'a> Drop for TestStruct<'a> {
+    fn drop(&mut self) {
+        self.1.set(self.1.get() + 1);
+    }
+}
+
+#[repr(align(64))]
+struct OverAligned(u32);
+
+#[dyn_dyn_impl]
+impl Base for OverAligned {
+    fn get(&self) -> u32 {
+        self.0
+    }
+}
+
+struct Zst;
+
+#[dyn_dyn_impl]
+impl Base for Zst {
+    fn get(&self) -> u32 {
+        7
+    }
+}
+
+#[test]
+fn test_thin_size() {
+    assert_eq!(
+        mem::size_of::<usize>(),
+        mem::size_of::<DynDynThinBox<dyn Base>>()
+    );
+    assert_eq!(
+        mem::size_of::<usize>(),
+        mem::size_of::<DynDynThinArc<dyn Base>>()
+    );
+}
+
+#[test]
+fn test_box_get_table_cached() {
+    let num_table_calls = Cell::new(0);
+    let mut ptr: DynDynThinBox<dyn CountingBase> =
+        DynDynThinBox::new(CountingStruct(&num_table_calls));
+
+    assert_eq!(1, num_table_calls.get());
+    assert_eq!(
+        &EMPTY_TABLE.1[..] as *const _,
+        DynDynThinBox::get_dyn_dyn_table(&ptr).into_slice() as *const _
+    );
+    let _ = dyn_dyn_cast!(CountingBase => CountingBase, &ptr);
+    let _ = dyn_dyn_cast!(mut CountingBase => CountingBase, &mut ptr);
+    assert_eq!(1, num_table_calls.get());
+}
+
+#[test]
+fn test_arc_get_table_cached() {
+    let num_table_calls = Cell::new(0);
+    let ptr: DynDynThinArc<dyn CountingBase> = DynDynThinArc::new(CountingStruct(&num_table_calls));
+    let clone = ptr.clone();
+
+    assert_eq!(1, num_table_calls.get());
+    assert_eq!(
+        &EMPTY_TABLE.1[..] as *const _,
+        DynDynThinArc::get_dyn_dyn_table(&clone).into_slice() as *const _
+    );
+    let _ = dyn_dyn_cast!(CountingBase => CountingBase, &ptr);
+    let _ = dyn_dyn_cast!(CountingBase => CountingBase, &clone);
+    assert_eq!(1, num_table_calls.get());
+}
+
+#[test]
+fn test_box_cast() {
+    let drops = Cell::new(0);
+    let mut ptr: DynDynThinBox<dyn Base> = DynDynThinBox::new(TestStruct(1, &drops));
+
+    dyn_dyn_cast!(mut Base => Trait, &mut ptr)
+        .ok()
+        .unwrap()
+        .set(2);
+    assert_eq!(2, ptr.get());
+    assert_eq!(
+        "TestStruct(2, Cell { value: 0 })",
+        format!("{:?}", dyn_dyn_cast!(Base => Debug, &ptr).ok().unwrap())
+    );
+
+    drop(ptr);
+    assert_eq!(1, drops.get());
+}
+
+#[test]
+fn test_arc_shared() {
+    let drops = Cell::new(0);
+    let mut ptr: DynDynThinArc<dyn Base> = DynDynThinArc::new(TestStruct(1, &drops));
+    let clone = ptr.clone();
+
+    assert!(DynDynThinArc::ptr_eq(&ptr, &clone));
+    assert_eq!(2, DynDynThinArc::strong_count(&ptr));
+    assert!(DynDynThinArc::get_mut(&mut ptr).is_none());
+    assert!(dyn_dyn_cast!(Base => Trait, &clone).is_ok());
+
+    drop(clone);
+    assert_eq!(0, drops.get());
+    assert_eq!(1, DynDynThinArc::strong_count(&ptr));
+    DynDynThinArc::get_mut(&mut ptr).unwrap();
+
+    drop(ptr);
+    assert_eq!(1, drops.get());
+}
+
+#[test]
+fn test_layout() {
+    let ptr: DynDynThinBox<dyn Base> = DynDynThinBox::new(OverAligned(3));
+    assert_eq!(3, ptr.get());
+    assert_eq!(0, (&*ptr as *const dyn Base).cast::<u8>() as usize % 64);
+
+    let ptr: DynDynThinArc<dyn Base> = DynDynThinArc::new(Zst);
+    assert_eq!(7, ptr.get());
+}
@@ -0,0 +1,102 @@
+use dyn_dyn::{dyn_dyn_base, dyn_dyn_impl, DynDynFat, DynDynView};
+
+#[dyn_dyn_base]
+trait Component {
+    fn name(&self) -> &'static str;
+}
+
+trait Health {
+    fn health(&self) -> u32;
+    fn damage(&mut self, amount: u32);
+}
+
+trait Position {
+    fn position(&self) -> (i32, i32);
+}
+
+trait Unimplemented {}
+
+#[derive(Debug)]
+struct Player(u32);
+
+#[dyn_dyn_impl(Health, Position)]
+impl Component for Player {
+    fn name(&self) -> &'static str {
+        "player"
+    }
+}
+
+impl Health for Player {
+    fn health(&self) -> u32 {
+        self.0
+    }
+
+    fn damage(&mut self, amount: u32) {
+        self.0 -= amount;
+    }
+}
+
+impl Position for Player {
+    fn position(&self) -> (i32, i32) {
+        (1, 2)
+    }
+}
+
+#[test]
+fn test_view_ref() {
+    let player = Player(100);
+    let component: &dyn Component = &player;
+
+    let view: DynDynView<dyn Component, dyn Health, _> = DynDynView::new(component).ok().unwrap();
+    assert_eq!(100, view.health());
+    assert_eq!("player", DynDynView::as_base(&view).name());
+    assert!(DynDynView::get_dyn_dyn_table(&view).can_cast::<dyn Position>());
+
+    let view: DynDynView<dyn Component, dyn Position, _> = DynDynView::recast(view).ok().unwrap();
+    assert_eq!((1, 2), view.position());
+
+    let view = DynDynView::recast::<dyn Unimplemented>(view).err().unwrap();
+    assert!(DynDynFat::cast_ref::<dyn Health>(DynDynView::as_fat(&view)).is_ok());
+
+    let component: &dyn Component = DynDynFat::unwrap(DynDynView::into_fat(view));
+    assert!(core::ptr::addr_eq(component, &player));
+}
+
+#[test]
+fn test_view_mut() {
+    let mut player = Player(100);
+    let component: &mut dyn Component = &mut player;
+
+    let mut view: DynDynView<dyn Component, dyn Health, _> =
+        DynDynView::new(component).ok().unwrap();
+    view.damage(30);
+    assert_eq!("player", DynDynView::as_base_mut(&mut view).name());
+    assert_eq!(70, view.health());
+}
+
+#[test]
+fn test_view_fails() {
+    let player = Player(100);
+    let component: &dyn Component = &player;
+
+    assert!(DynDynView::<dyn Component, dyn Unimplemented, _>::new(component).is_err());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_view_arc() {
+    extern crate alloc;
+
+    use alloc::sync::Arc;
+
+    let arc: Arc<dyn Component> = Arc::new(Player(100));
+    let view: DynDynView<dyn Component, dyn Health, _> = DynDynView::new(arc).ok().unwrap();
+    let clone = view.clone();
+
+    assert_eq!(100, clone.health());
+    assert!(core::ptr::addr_eq(&*view, &*clone));
+
+    let arc: Arc<dyn Component> = DynDynFat::unwrap(DynDynView::into_fat(view));
+    assert_eq!(2, Arc::strong_count(&arc));
+    assert_eq!("player", arc.name());
+}
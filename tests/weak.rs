@@ -0,0 +1,120 @@
+#![cfg(feature = "alloc")]
+
+use dyn_dyn::{dyn_dyn_base, dyn_dyn_cast, dyn_dyn_impl, DynDynFat, DynDynTable, GetDynDynTable};
+use std::rc::{self, Rc};
+use std::sync::{self, Arc};
+
+#[dyn_dyn_base]
+trait Listener {}
+
+trait Resize {
+    fn on_resize(&self) -> u32;
+}
+
+struct Window(u32);
+struct Logger;
+
+#[dyn_dyn_impl(Resize)]
+impl Listener for Window {}
+
+#[dyn_dyn_impl]
+impl Listener for Logger {}
+
+impl Resize for Window {
+    fn on_resize(&self) -> u32 {
+        self.0
+    }
+}
+
+#[test]
+fn test_weak_rc() {
+    let strong: Rc<dyn Listener> = Rc::new(Window(1));
+    let weak: rc::Weak<dyn Listener> = Rc::downgrade(&strong);
+
+    let resize: rc::Weak<dyn Resize> = dyn_dyn_cast!(move Listener => Resize, weak).ok().unwrap();
+    assert_eq!(1, Rc::strong_count(&strong));
+    assert_eq!(1, resize.upgrade().unwrap().on_resize());
+}
+
+#[test]
+fn test_weak_arc() {
+    let strong: Arc<dyn Listener> = Arc::new(Window(2));
+    let weak: sync::Weak<dyn Listener> = Arc::downgrade(&strong);
+
+    let resize: sync::Weak<dyn Resize> = dyn_dyn_cast!(move Listener => Resize, weak).ok().unwrap();
+    assert_eq!(2, resize.upgrade().unwrap().on_resize());
+}
+
+#[test]
+fn test_weak_filter() {
+    let listeners: Vec<Rc<dyn Listener>> =
+        vec![Rc::new(Window(1)), Rc::new(Logger), Rc::new(Window(2))];
+    let weak: Vec<rc::Weak<dyn Listener>> = listeners.iter().map(Rc::downgrade).collect();
+
+    let resize: Vec<_> = weak
+        .iter()
+        .filter_map(|w| dyn_dyn_cast!(move Listener => Resize, w.clone()).ok())
+        .collect();
+
+    assert_eq!(2, resize.len());
+    assert_eq!(
+        vec![1, 2],
+        resize
+            .iter()
+            .map(|r| r.upgrade().unwrap().on_resize())
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_weak_dropped() {
+    let strong: Rc<dyn Listener> = Rc::new(Window(1));
+    let weak: rc::Weak<dyn Listener> = Rc::downgrade(&strong);
+    drop(strong);
+
+    let table = GetDynDynTable::<dyn Listener>::get_dyn_dyn_table(&weak);
+    assert!(table.is_empty());
+    assert!(!DynDynTable::same_concrete_type(&table, &table));
+    assert_eq!(0, table.layout().size());
+    assert!(dyn_dyn_cast!(move Listener => Resize, weak).is_err());
+
+    let other: sync::Weak<dyn Listener> = Arc::downgrade(&(Arc::new(Logger) as Arc<dyn Listener>));
+    let other_table = GetDynDynTable::<dyn Listener>::get_dyn_dyn_table(&other);
+    assert!(!DynDynTable::same_concrete_type(&table, &other_table));
+}
+
+#[test]
+fn test_weak_fat() {
+    let strong: DynDynFat<dyn Listener, Rc<dyn Listener>> = DynDynFat::new(Rc::new(Window(3)));
+    let weak = DynDynFat::downgrade_rc(&strong);
+
+    assert!(dyn_dyn_cast!(move Listener => Resize, DynDynFat::downgrade_rc(&strong)).is_ok());
+    assert_eq!(
+        3,
+        dyn_dyn_cast!(move Listener => Resize, DynDynFat::upgrade_rc(&weak).unwrap())
+            .ok()
+            .unwrap()
+            .on_resize()
+    );
+
+    drop(strong);
+
+    // The table was cached while the object was live, so it's still available.
+    assert_eq!(1, DynDynFat::get_dyn_dyn_table(&weak).len());
+    assert!(DynDynFat::upgrade_rc(&weak).is_none());
+
+    let resize: rc::Weak<dyn Resize> = dyn_dyn_cast!(move Listener => Resize, weak).ok().unwrap();
+    assert!(resize.upgrade().is_none());
+}
+
+#[test]
+fn test_weak_fat_arc() {
+    let strong: DynDynFat<dyn Listener, Arc<dyn Listener>> = DynDynFat::new(Arc::new(Window(4)));
+    let weak = DynDynFat::downgrade_arc(&strong);
+
+    let resize: sync::Weak<dyn Resize> = dyn_dyn_cast!(move Listener => Resize, weak).ok().unwrap();
+    assert_eq!(4, resize.upgrade().unwrap().on_resize());
+
+    drop(strong);
+    assert!(resize.upgrade().is_none());
+}
@@ -0,0 +1,93 @@
+use core::cell::Cell;
+use core::mem::ManuallyDrop;
+use dyn_dyn::{dyn_dyn_base, dyn_dyn_cast, dyn_dyn_impl, DynDynTable};
+
+#[dyn_dyn_base]
+trait Base {}
+
+trait Trait {
+    fn get(&self) -> u32;
+    fn set(&mut self, val: u32);
+}
+
+struct StructA(u32);
+struct StructB;
+
+#[dyn_dyn_impl(Trait)]
+impl Base for StructA {}
+
+#[dyn_dyn_impl]
+impl Base for StructB {}
+
+impl Trait for StructA {
+    fn get(&self) -> u32 {
+        self.0
+    }
+
+    fn set(&mut self, val: u32) {
+        self.0 = val;
+    }
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_rc_cell() {
+    use std::rc::Rc;
+
+    let rc: Rc<Cell<dyn Base>> = Rc::new(Cell::new(StructA(1)));
+    let mut rc: Rc<Cell<dyn Trait>> = dyn_dyn_cast!(move Base => Trait [Cell<$>], rc)
+        .ok()
+        .unwrap();
+
+    Rc::get_mut(&mut rc).unwrap().get_mut().set(3);
+    assert_eq!(3, Rc::get_mut(&mut rc).unwrap().get_mut().get());
+}
+
+#[test]
+fn test_cell_fail() {
+    let cell = Cell::new(StructB);
+    let cell: &Cell<dyn Base> = &cell;
+
+    assert!(dyn_dyn_cast!(Base => Trait [Cell<$>], cell).is_err());
+}
+
+#[test]
+fn test_cell() {
+    let cell = Cell::new(StructA(1));
+    let cell: &mut Cell<dyn Base> = &mut { cell };
+
+    let cast: &mut Cell<dyn Trait> = dyn_dyn_cast!(mut Base => Trait [Cell<$>], cell)
+        .ok()
+        .unwrap();
+    cast.get_mut().set(4);
+
+    assert_eq!(4, cast.get_mut().get());
+}
+
+#[test]
+fn test_manually_drop() {
+    let val = ManuallyDrop::new(StructA(5));
+    let val: &ManuallyDrop<dyn Base> = &val;
+
+    let cast: &ManuallyDrop<dyn Trait> = dyn_dyn_cast!(Base => Trait [ManuallyDrop<$>], val)
+        .ok()
+        .unwrap();
+    assert_eq!(5, cast.get());
+}
+
+#[test]
+fn test_wrapper_concrete_type() {
+    let cell = Cell::new(StructA(6));
+    let table = dyn_dyn::introspect::<Cell<dyn Base>>(&cell);
+
+    assert!(table.can_cast::<dyn Trait>());
+    assert!(!table.is::<StructA>());
+    assert!(!table.is::<Cell<StructA>>());
+
+    let val = ManuallyDrop::new(StructA(7));
+    let table = dyn_dyn::introspect::<ManuallyDrop<dyn Base>>(&val);
+
+    assert!(table.can_cast::<dyn Trait>());
+    assert!(!table.is::<StructA>());
+    assert!(!DynDynTable::same_concrete_type(&table, &table));
+}